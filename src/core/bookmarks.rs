@@ -0,0 +1,148 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::config_dir;
+use super::uri::{file_uri, file_uri_path};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub uri: String,
+    pub label: Option<String>,
+}
+
+impl Bookmark {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            uri: file_uri(path),
+            label: None,
+        }
+    }
+
+    /// The local path of a `file://` bookmark.
+    pub fn path(&self) -> Option<PathBuf> {
+        file_uri_path(&self.uri)
+    }
+
+    /// The label, or else the folder name.
+    pub fn name(&self) -> String {
+        if let Some(label) = &self.label {
+            return label.clone();
+        }
+        self.path()
+            .map(|path| match path.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => path.display().to_string(),
+            })
+            .unwrap_or_else(|| self.uri.clone())
+    }
+}
+
+pub fn bookmarks_file() -> Option<PathBuf> {
+    config_dir().map(|config| config.join("gtk-3.0").join("bookmarks"))
+}
+
+pub fn load_bookmarks() -> Vec<Bookmark> {
+    bookmarks_file()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .map(|content| parse_bookmarks(&content))
+        .unwrap_or_default()
+}
+
+pub fn save_bookmarks(bookmarks: &[Bookmark]) -> io::Result<()> {
+    let file = bookmarks_file()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Config directory not found"))?;
+    write_bookmarks(&file, bookmarks)
+}
+
+fn write_bookmarks(file: &Path, bookmarks: &[Bookmark]) -> io::Result<()> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temporary = file.with_extension("tmp");
+    fs::write(&temporary, format_bookmarks(bookmarks))?;
+    fs::rename(temporary, file)
+}
+
+fn format_bookmarks(bookmarks: &[Bookmark]) -> String {
+    bookmarks
+        .iter()
+        .map(|bookmark| match &bookmark.label {
+            Some(label) => format!("{} {label}\n", bookmark.uri),
+            None => format!("{}\n", bookmark.uri),
+        })
+        .collect()
+}
+
+pub fn parse_bookmarks(content: &str) -> Vec<Bookmark> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once(' ') {
+            Some((uri, label)) => Bookmark {
+                uri: uri.to_owned(),
+                label: Some(label.trim().to_owned()).filter(|label| !label.is_empty()),
+            },
+            None => Bookmark {
+                uri: line.to_owned(),
+                label: None,
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bookmarks() {
+        let content =
+            "file:///home/me/My%20Projects Work\nfile:///srv/data\n\nsftp://host/share Server\n";
+
+        let bookmarks = parse_bookmarks(content);
+
+        assert_eq!(bookmarks.len(), 3);
+        assert_eq!(
+            bookmarks[0].path(),
+            Some(PathBuf::from("/home/me/My Projects"))
+        );
+        assert_eq!(bookmarks[0].name(), "Work");
+        assert_eq!(bookmarks[1].name(), "data");
+        assert_eq!(bookmarks[2].path(), None);
+        assert_eq!(bookmarks[2].name(), "Server");
+    }
+
+    #[test]
+    fn writes_bookmarks_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("gtk-3.0/bookmarks");
+        let mut bookmarks = vec![
+            Bookmark::new(Path::new("/srv/my data")),
+            Bookmark {
+                uri: "sftp://host/share".to_owned(),
+                label: Some("Server".to_owned()),
+            },
+        ];
+        bookmarks[0].label = Some("Data".to_owned());
+
+        write_bookmarks(&file, &bookmarks).unwrap();
+
+        let content = fs::read_to_string(&file).unwrap();
+        assert_eq!(
+            content,
+            "file:///srv/my%20data Data\nsftp://host/share Server\n"
+        );
+        assert_eq!(parse_bookmarks(&content), bookmarks);
+    }
+
+    #[test]
+    fn encodes_file_uris() {
+        let path = Path::new("/home/me/a b#ć");
+        let bookmark = Bookmark::new(path);
+
+        assert_eq!(bookmark.uri, "file:///home/me/a%20b%23%C4%87");
+        assert_eq!(bookmark.path().as_deref(), Some(path));
+    }
+}
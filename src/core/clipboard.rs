@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use super::TransferMode;
+use super::uri::{file_uri, file_uri_path};
+
+/// The clipboard target of GNOME file managers, which also says whether the files were cut.
+pub const GNOME_COPIED_FILES: &str = "x-special/gnome-copied-files";
+pub const URI_LIST: &str = "text/uri-list";
+
+/// The `text/uri-list` content of `paths`, one CRLF-terminated URI per line.
+pub fn format_uri_list(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| format!("{}\r\n", file_uri(path)))
+        .collect()
+}
+
+/// The local paths of a `text/uri-list`, skipping comments and other schemes.
+pub fn parse_uri_list(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(file_uri_path)
+        .collect()
+}
+
+/// The `x-special/gnome-copied-files` content: `copy` or `cut`, then a URI per line.
+pub fn format_gnome_copied_files(mode: TransferMode, paths: &[PathBuf]) -> String {
+    let action = match mode {
+        TransferMode::Copy => "copy",
+        TransferMode::Move => "cut",
+    };
+    let uris: Vec<String> = paths.iter().map(|path| file_uri(path)).collect();
+    format!("{action}\n{}", uris.join("\n"))
+}
+
+pub fn parse_gnome_copied_files(content: &str) -> Option<(TransferMode, Vec<PathBuf>)> {
+    let mut lines = content.lines().map(str::trim);
+    let mode = match lines.next()? {
+        "copy" => TransferMode::Copy,
+        "cut" => TransferMode::Move,
+        _ => return None,
+    };
+    let paths = lines
+        .filter(|line| !line.is_empty())
+        .filter_map(file_uri_path)
+        .collect();
+    Some((mode, paths))
+}
+
+/// The paths as plain text, for pasting into editors and terminals.
+pub fn format_plain_paths(paths: &[PathBuf]) -> String {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    paths.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_uri_lists() {
+        let paths = vec![PathBuf::from("/home/me/a b"), PathBuf::from("/srv/c")];
+
+        let content = format_uri_list(&paths);
+
+        assert_eq!(content, "file:///home/me/a%20b\r\nfile:///srv/c\r\n");
+        assert_eq!(parse_uri_list(&content), paths);
+        assert_eq!(
+            parse_uri_list("# comment\r\nhttp://example.com\r\nfile:///srv/c\r\n"),
+            vec![PathBuf::from("/srv/c")]
+        );
+    }
+
+    #[test]
+    fn round_trips_gnome_copied_files() {
+        let paths = vec![PathBuf::from("/home/me/a b"), PathBuf::from("/srv/c")];
+
+        let content = format_gnome_copied_files(TransferMode::Move, &paths);
+
+        assert_eq!(content, "cut\nfile:///home/me/a%20b\nfile:///srv/c");
+        assert_eq!(
+            parse_gnome_copied_files(&content),
+            Some((TransferMode::Move, paths))
+        );
+        assert_eq!(
+            parse_gnome_copied_files("copy\nfile:///srv/c\n"),
+            Some((TransferMode::Copy, vec![PathBuf::from("/srv/c")]))
+        );
+        assert_eq!(parse_gnome_copied_files("file:///srv/c"), None);
+    }
+}
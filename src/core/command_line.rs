@@ -0,0 +1,190 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use super::normalize_path;
+
+/// The arguments the application is launched with.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CommandLine {
+    pub path: Option<PathBuf>,
+    pub select: Option<PathBuf>,
+    pub show_hidden: bool,
+    pub new_window: bool,
+    pub view: Option<String>,
+}
+
+impl CommandLine {
+    /// Parses the arguments following the program name. Options take their value
+    /// either as `--option=value` or as the next argument.
+    pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Self, String> {
+        let mut command_line = Self::default();
+        let mut args = args.into_iter();
+        let mut options_ended = false;
+        while let Some(arg) = args.next() {
+            let text = arg.to_string_lossy();
+            if options_ended || !text.starts_with("--") {
+                if command_line.path.replace(PathBuf::from(arg)).is_some() {
+                    return Err("Only one path can be given".to_owned());
+                }
+                continue;
+            }
+
+            let (name, inline) = match text.split_once('=') {
+                Some((name, value)) => (name, Some(OsString::from(value))),
+                None => (text.as_ref(), None),
+            };
+            let mut value = |name: &str| {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{name} needs a value"))
+            };
+            if inline.is_some() && ["--", "--show-hidden", "--new-window"].contains(&name) {
+                return Err(format!("{name} takes no value"));
+            }
+            match name {
+                "--" => options_ended = true,
+                "--show-hidden" => command_line.show_hidden = true,
+                "--new-window" => command_line.new_window = true,
+                "--select" => command_line.select = Some(PathBuf::from(value(name)?)),
+                "--view" => command_line.view = Some(value(name)?.to_string_lossy().into_owned()),
+                _ => return Err(format!("Unknown option {name}")),
+            }
+        }
+        Ok(command_line)
+    }
+
+    /// The folder to open and the item to select in it, with relative paths taken
+    /// from `cwd` and `.` and `..` resolved. A path that is not a folder is selected
+    /// in its parent folder; paths that do not exist are an error.
+    pub fn location(&self, cwd: &Path) -> Result<Option<(PathBuf, Option<PathBuf>)>, String> {
+        let select = self
+            .select
+            .as_ref()
+            .map(|select| existing(normalize_path(&cwd.join(select))))
+            .transpose()?;
+        let path = match (&self.path, &select) {
+            (Some(path), _) => existing(normalize_path(&cwd.join(path)))?,
+            (None, Some(select)) => select.clone(),
+            (None, None) => return Ok(None),
+        };
+        if path.is_dir() {
+            return Ok(Some((path, select)));
+        }
+        Ok(path
+            .parent()
+            .map(|parent| (parent.to_path_buf(), select.or(Some(path.clone())))))
+    }
+}
+
+fn existing(path: PathBuf) -> Result<PathBuf, String> {
+    match path.symlink_metadata() {
+        Ok(_) => Ok(path),
+        Err(error) => Err(format!("{}: {error}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CommandLine, String> {
+        CommandLine::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn parses_options_and_path() {
+        let command_line = parse(&["--show-hidden", "--view=grid", "docs", "--new-window"]);
+
+        assert_eq!(
+            command_line,
+            Ok(CommandLine {
+                path: Some(PathBuf::from("docs")),
+                select: None,
+                show_hidden: true,
+                new_window: true,
+                view: Some("grid".to_owned()),
+            })
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        assert_eq!(
+            parse(&[".."]).unwrap().location(&docs),
+            Ok(Some((dir.path().to_path_buf(), None)))
+        );
+        assert_eq!(
+            parse(&["../docs/."]).unwrap().location(&docs),
+            Ok(Some((docs.clone(), None)))
+        );
+    }
+
+    #[test]
+    fn takes_values_from_the_next_argument() {
+        let command_line = parse(&["--select", "a.txt", "--view", "details"]).unwrap();
+
+        assert_eq!(command_line.select, Some(PathBuf::from("a.txt")));
+        assert_eq!(command_line.view.as_deref(), Some("details"));
+        assert_eq!(command_line.path, None);
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert!(parse(&["--verbose"]).is_err());
+        assert!(parse(&["--select"]).is_err());
+        assert!(parse(&["a", "b"]).is_err());
+        assert!(parse(&["--show-hidden=false"]).is_err());
+        assert!(parse(&["--new-window=yes"]).is_err());
+        assert_eq!(
+            parse(&["--", "--odd"]).unwrap().path,
+            Some(PathBuf::from("--odd"))
+        );
+    }
+
+    #[test]
+    fn selects_files_in_their_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/a.txt"), b"a").unwrap();
+
+        let folder = parse(&["docs"]).unwrap();
+        assert_eq!(
+            folder.location(dir.path()),
+            Ok(Some((dir.path().join("docs"), None)))
+        );
+
+        let file = parse(&["docs/a.txt"]).unwrap();
+        assert_eq!(
+            file.location(dir.path()),
+            Ok(Some((
+                dir.path().join("docs"),
+                Some(dir.path().join("docs/a.txt"))
+            )))
+        );
+
+        let select = parse(&["--select=docs/a.txt"]).unwrap();
+        assert_eq!(select.location(dir.path()), file.location(dir.path()));
+
+        assert_eq!(parse(&[]).unwrap().location(dir.path()), Ok(None));
+    }
+
+    #[test]
+    fn rejects_paths_that_do_not_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+
+        assert!(
+            parse(&["no/such/dir"])
+                .unwrap()
+                .location(dir.path())
+                .is_err()
+        );
+        assert!(
+            parse(&["docs", "--select=docs/missing.txt"])
+                .unwrap()
+                .location(dir.path())
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::sort::{compare_names, extension};
+use super::user_dirs::{UserDir, user_dirs};
+
+/// What a new entry is created as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NewEntry {
+    Folder,
+    File,
+    /// A copy of a file of the Templates folder.
+    Document(PathBuf),
+}
+
+impl NewEntry {
+    /// The name the entry gets until the user renames it.
+    pub fn default_name(&self) -> String {
+        match self {
+            NewEntry::Folder => "New Folder".to_owned(),
+            NewEntry::File => "New File".to_owned(),
+            NewEntry::Document(template) => template
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "New Document".to_owned()),
+        }
+    }
+
+    /// The path in `dir` the entry gets, numbered like `New Folder 2` when its
+    /// name is taken.
+    pub fn free_path(&self, dir: &Path) -> PathBuf {
+        let name = self.default_name();
+        let extension = match self {
+            NewEntry::Folder => "",
+            NewEntry::File | NewEntry::Document(_) => extension(&name),
+        };
+        let stem = match extension {
+            "" => name.as_str(),
+            extension => &name[..name.len() - extension.len() - 1],
+        };
+
+        (1..)
+            .map(|number| match (number, extension) {
+                (1, _) => dir.join(&name),
+                (number, "") => dir.join(format!("{stem} {number}")),
+                (number, extension) => dir.join(format!("{stem} {number}.{extension}")),
+            })
+            .find(|path| path.symlink_metadata().is_err())
+            .unwrap_or_else(|| dir.join(&name))
+    }
+}
+
+/// Creates the entry at `path`, failing rather than replacing what is there.
+pub fn create(path: &Path, entry: &NewEntry) -> io::Result<()> {
+    match entry {
+        NewEntry::Folder => fs::create_dir(path),
+        NewEntry::File => fs::File::create_new(path).map(|_| ()),
+        NewEntry::Document(template) => {
+            let mut source = fs::File::open(template)?;
+            let mut target = fs::File::create_new(path)?;
+            io::copy(&mut source, &mut target).map(|_| ())
+        }
+    }
+}
+
+/// The files of the Templates folder new documents are created from, by name.
+pub fn templates() -> Vec<PathBuf> {
+    user_dirs()
+        .into_iter()
+        .find(|(dir, _)| *dir == UserDir::Templates)
+        .map(|(_, dir)| read_templates(&dir))
+        .unwrap_or_default()
+}
+
+fn read_templates(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    templates.sort_by(|a, b| {
+        let name = |path: &Path| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        };
+        compare_names(&name(a), &name(b), true, true)
+    });
+    templates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_taken_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = NewEntry::Folder;
+        let document = NewEntry::Document(PathBuf::from("/templates/Letter.odt"));
+
+        assert_eq!(folder.free_path(dir.path()), dir.path().join("New Folder"));
+        create(&folder.free_path(dir.path()), &folder).unwrap();
+        create(&folder.free_path(dir.path()), &folder).unwrap();
+        assert_eq!(
+            folder.free_path(dir.path()),
+            dir.path().join("New Folder 3")
+        );
+
+        fs::write(dir.path().join("Letter.odt"), "").unwrap();
+        assert_eq!(
+            document.free_path(dir.path()),
+            dir.path().join("Letter 2.odt")
+        );
+    }
+
+    #[test]
+    fn creates_without_replacing() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("template.txt");
+        fs::write(&template, "Dear").unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        fs::write(dir.path().join(".hidden"), "").unwrap();
+
+        let file = dir.path().join("file");
+        create(&file, &NewEntry::File).unwrap();
+        assert!(create(&file, &NewEntry::Document(template.clone())).is_err());
+        assert_eq!(fs::read(&file).unwrap(), b"");
+
+        let letter = dir.path().join("letter.txt");
+        create(&letter, &NewEntry::Document(template.clone())).unwrap();
+        assert_eq!(fs::read(&letter).unwrap(), b"Dear");
+
+        assert_eq!(read_templates(dir.path()), [file, letter, template]);
+    }
+}
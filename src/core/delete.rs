@@ -0,0 +1,118 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::transfer::TransferObserver;
+
+/// Entries that cannot be removed are skipped, with their errors in `errors`.
+pub fn delete_tree(
+    path: &Path,
+    observer: &mut dyn TransferObserver,
+    errors: &mut Vec<String>,
+) -> io::Result<()> {
+    let metadata = match path.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            errors.push(describe(path, &error));
+            return Ok(());
+        }
+    };
+    if !metadata.is_dir() {
+        match fs::remove_file(path) {
+            Ok(()) => {
+                observer.copied(metadata.len())?;
+                observer.file_copied()?;
+            }
+            Err(error) => errors.push(describe(path, &error)),
+        }
+        return Ok(());
+    }
+
+    let failed = errors.len();
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries {
+                match entry {
+                    Ok(entry) => delete_tree(&entry.path(), observer, errors)?,
+                    Err(error) => errors.push(describe(path, &error)),
+                }
+            }
+        }
+        Err(error) => errors.push(describe(path, &error)),
+    }
+    if let Err(error) = fs::remove_dir(path)
+        && errors.len() == failed
+    {
+        errors.push(describe(path, &error));
+    }
+    Ok(())
+}
+
+fn describe(path: &Path, error: &io::Error) -> String {
+    format!("{}: {error}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter {
+        bytes: u64,
+        files: u64,
+    }
+
+    impl TransferObserver for Counter {
+        fn copied(&mut self, bytes: u64) -> io::Result<()> {
+            self.bytes += bytes;
+            Ok(())
+        }
+
+        fn file_copied(&mut self) -> io::Result<()> {
+            self.files += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn deletes_trees_and_counts_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir_all(root.join("nested/empty")).unwrap();
+        fs::write(root.join("a.txt"), b"abc").unwrap();
+        fs::write(root.join("nested/b.txt"), b"de").unwrap();
+
+        let mut counter = Counter { bytes: 0, files: 0 };
+        let mut errors = Vec::new();
+        delete_tree(&root, &mut counter, &mut errors).unwrap();
+
+        assert!(errors.is_empty());
+        assert!(!root.exists());
+        assert_eq!((counter.bytes, counter.files), (5, 2));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn goes_on_past_entries_it_cannot_delete() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir_all(root.join("locked")).unwrap();
+        fs::write(root.join("locked/kept.txt"), b"kept").unwrap();
+        fs::write(root.join("gone.txt"), b"gone").unwrap();
+        fs::set_permissions(root.join("locked"), fs::Permissions::from_mode(0o555)).unwrap();
+
+        let mut errors = Vec::new();
+        delete_tree(&root, &mut (), &mut errors).unwrap();
+
+        assert!(!root.join("gone.txt").exists());
+        // Running as root ignores the permissions.
+        if root.join("locked").exists() {
+            fs::set_permissions(root.join("locked"), fs::Permissions::from_mode(0o755)).unwrap();
+            assert_eq!(errors.len(), 1);
+            assert!(errors[0].contains("kept.txt"));
+        } else {
+            assert!(errors.is_empty());
+        }
+    }
+}
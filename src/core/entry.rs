@@ -0,0 +1,421 @@
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryType {
+    Directory,
+    File,
+    Symlink,
+    BlockDevice,
+    CharDevice,
+    Fifo,
+    Socket,
+    Unknown,
+}
+
+impl From<fs::FileType> for EntryType {
+    fn from(value: fs::FileType) -> Self {
+        #[cfg(unix)]
+        use std::os::unix::fs::FileTypeExt;
+
+        if value.is_symlink() {
+            return Self::Symlink;
+        }
+        if value.is_dir() {
+            return Self::Directory;
+        }
+        if value.is_file() {
+            return Self::File;
+        }
+
+        #[cfg(unix)]
+        {
+            if value.is_block_device() {
+                return Self::BlockDevice;
+            }
+            if value.is_char_device() {
+                return Self::CharDevice;
+            }
+            if value.is_fifo() {
+                return Self::Fifo;
+            }
+            if value.is_socket() {
+                return Self::Socket;
+            }
+        }
+
+        Self::Unknown
+    }
+}
+
+/// Captured by a single `lstat`, plus a `stat` for symlinks, while the directory is listed.
+#[derive(Debug, Clone)]
+pub struct DirEntryInfo {
+    _name: OsString,
+    _path: PathBuf,
+    _entry_type: EntryType,
+    _symlink_target: Option<PathBuf>,
+    _target_type: Option<EntryType>,
+    _size: u64,
+    _modified: Option<SystemTime>,
+    _created: Option<SystemTime>,
+    _accessed: Option<SystemTime>,
+    _permissions: u32,
+    _uid: u32,
+    _gid: u32,
+    _inode: u64,
+    _device: u64,
+    _is_hidden: bool,
+}
+
+impl DirEntryInfo {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let metadata = fs::symlink_metadata(path)?;
+        let entry_type = EntryType::from(metadata.file_type());
+        let (symlink_target, target_type) = if entry_type == EntryType::Symlink {
+            let target = fs::read_link(path).ok();
+            let target_type = fs::metadata(path)
+                .ok()
+                .map(|target| EntryType::from(target.file_type()));
+            (target, target_type)
+        } else {
+            (None, None)
+        };
+
+        let name = path
+            .file_name()
+            .map(OsStr::to_os_string)
+            .unwrap_or_else(|| path.as_os_str().to_os_string());
+
+        let (uid, gid) = owner(&metadata);
+        let (inode, device) = node(&metadata);
+
+        Ok(Self {
+            _is_hidden: is_hidden(&name, &metadata),
+            _name: name,
+            _path: path.to_path_buf(),
+            _entry_type: entry_type,
+            _symlink_target: symlink_target,
+            _target_type: target_type,
+            _size: metadata.len(),
+            _modified: metadata.modified().ok(),
+            _created: metadata.created().ok(),
+            _accessed: metadata.accessed().ok(),
+            _permissions: permissions(&metadata),
+            _uid: uid,
+            _gid: gid,
+            _inode: inode,
+            _device: device,
+        })
+    }
+
+    pub fn name(&self) -> &OsStr {
+        &self._name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self._path
+    }
+
+    pub fn entry_type(&self) -> EntryType {
+        self._entry_type
+    }
+
+    pub fn symlink_target(&self) -> Option<&Path> {
+        self._symlink_target.as_deref()
+    }
+
+    /// The type of the entry with symlinks resolved, `None` for a broken symlink.
+    pub fn target_type(&self) -> Option<EntryType> {
+        match self._entry_type {
+            EntryType::Symlink => self._target_type,
+            entry_type => Some(entry_type),
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.target_type() == Some(EntryType::Directory)
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self._entry_type == EntryType::Symlink
+    }
+
+    pub fn is_broken_symlink(&self) -> bool {
+        self.is_symlink() && self._target_type.is_none()
+    }
+
+    pub fn size(&self) -> u64 {
+        self._size
+    }
+
+    pub fn modified(&self) -> Option<SystemTime> {
+        self._modified
+    }
+
+    pub fn created(&self) -> Option<SystemTime> {
+        self._created
+    }
+
+    pub fn accessed(&self) -> Option<SystemTime> {
+        self._accessed
+    }
+
+    /// Unix permission bits (`st_mode & 0o7777`); on Windows only the write bit is meaningful.
+    pub fn permissions(&self) -> u32 {
+        self._permissions
+    }
+
+    /// The permissions in `ls -l` notation, e.g. `drwxr-xr-x`.
+    pub fn permissions_text(&self) -> String {
+        let mut text = String::with_capacity(10);
+        text.push(match self._entry_type {
+            EntryType::Directory => 'd',
+            EntryType::Symlink => 'l',
+            EntryType::BlockDevice => 'b',
+            EntryType::CharDevice => 'c',
+            EntryType::Fifo => 'p',
+            EntryType::Socket => 's',
+            EntryType::File | EntryType::Unknown => '-',
+        });
+
+        let mode = self._permissions;
+        for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')]
+        {
+            let bits = (mode >> shift) & 0o7;
+            text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            text.push(match (bits & 0o1 != 0, mode & special != 0) {
+                (true, true) => special_char,
+                (false, true) => special_char.to_ascii_uppercase(),
+                (true, false) => 'x',
+                (false, false) => '-',
+            });
+        }
+
+        text
+    }
+
+    pub fn uid(&self) -> u32 {
+        self._uid
+    }
+
+    pub fn gid(&self) -> u32 {
+        self._gid
+    }
+
+    pub fn inode(&self) -> u64 {
+        self._inode
+    }
+
+    pub fn device(&self) -> u64 {
+        self._device
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self._is_hidden
+    }
+}
+
+#[cfg(unix)]
+fn is_hidden(name: &OsStr, _: &Metadata) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    name.as_bytes().starts_with(b".")
+}
+
+#[cfg(windows)]
+fn is_hidden(_: &OsStr, metadata: &Metadata) -> bool {
+    use std::os::windows::prelude::*;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x00000002;
+
+    metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(windows)]
+fn permissions(metadata: &Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o666
+    }
+}
+
+#[cfg(unix)]
+fn owner(metadata: &Metadata) -> (u32, u32) {
+    use std::os::unix::fs::MetadataExt;
+
+    (metadata.uid(), metadata.gid())
+}
+
+#[cfg(windows)]
+fn owner(_: &Metadata) -> (u32, u32) {
+    (0, 0)
+}
+
+#[cfg(unix)]
+fn node(metadata: &Metadata) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+
+    (metadata.ino(), metadata.dev())
+}
+
+#[cfg(windows)]
+fn node(_: &Metadata) -> (u64, u64) {
+    (0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_regular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, b"hello").unwrap();
+
+        let entry = DirEntryInfo::read(&path).unwrap();
+
+        assert_eq!(entry.name(), "notes.txt");
+        assert_eq!(entry.path(), path);
+        assert_eq!(entry.entry_type(), EntryType::File);
+        assert_eq!(entry.size(), 5);
+        assert!(!entry.is_dir());
+        assert!(!entry.is_hidden());
+        assert!(entry.modified().is_some());
+        assert!(entry.symlink_target().is_none());
+    }
+
+    #[test]
+    fn reads_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested");
+        fs::create_dir(&path).unwrap();
+
+        let entry = DirEntryInfo::read(&path).unwrap();
+
+        assert_eq!(entry.entry_type(), EntryType::Directory);
+        assert!(entry.is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_hidden_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".profile");
+        fs::write(&path, b"").unwrap();
+
+        assert!(DirEntryInfo::read(&path).unwrap().is_hidden());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_unix_metadata() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.sh");
+        fs::write(&path, b"#!/bin/sh").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+
+        let entry = DirEntryInfo::read(&path).unwrap();
+
+        assert_eq!(entry.permissions(), 0o750);
+        assert_eq!(entry.uid(), metadata.uid());
+        assert_eq!(entry.gid(), metadata.gid());
+        assert_eq!(entry.inode(), metadata.ino());
+        assert_eq!(entry.device(), metadata.dev());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn formats_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("tool");
+        let nested = dir.path().join("shared");
+        fs::write(&file, b"").unwrap();
+        fs::create_dir(&nested).unwrap();
+
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o4754)).unwrap();
+        fs::set_permissions(&nested, fs::Permissions::from_mode(0o1777)).unwrap();
+
+        assert_eq!(
+            DirEntryInfo::read(&file).unwrap().permissions_text(),
+            "-rwsr-xr--"
+        );
+        assert_eq!(
+            DirEntryInfo::read(&nested).unwrap().permissions_text(),
+            "drwxrwxrwt"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_symlink_to_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        fs::create_dir(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let entry = DirEntryInfo::read(&link).unwrap();
+
+        assert_eq!(entry.entry_type(), EntryType::Symlink);
+        assert_eq!(entry.symlink_target(), Some(target.as_path()));
+        assert_eq!(entry.target_type(), Some(EntryType::Directory));
+        assert!(entry.is_dir());
+        assert!(!entry.is_broken_symlink());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn detects_broken_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("dangling");
+        std::os::unix::fs::symlink(dir.path().join("missing"), &link).unwrap();
+
+        let entry = DirEntryInfo::read(&link).unwrap();
+
+        assert!(entry.is_broken_symlink());
+        assert_eq!(entry.target_type(), None);
+        assert!(!entry.is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_fifo() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pipe");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        assert_eq!(
+            DirEntryInfo::read(&path).unwrap().entry_type(),
+            EntryType::Fifo
+        );
+    }
+
+    #[test]
+    fn fails_for_missing_path() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert!(DirEntryInfo::read(&dir.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+const MAX_ENTRIES: usize = 50;
+
+/// Browser-like navigation history; visiting a new location drops the forward entries.
+#[derive(Debug, Default)]
+pub struct History {
+    back: Vec<String>,
+    current: Option<String>,
+    forward: Vec<String>,
+}
+
+impl History {
+    pub fn visit(&mut self, location: String) {
+        if self.current.as_ref() == Some(&location) {
+            return;
+        }
+        if let Some(current) = self.current.replace(location) {
+            self.back.push(current);
+            if self.back.len() > MAX_ENTRIES {
+                self.back.remove(0);
+            }
+        }
+        self.forward.clear();
+    }
+
+    /// The location `steps` entries back, if the history goes back that far.
+    pub fn back_location(&self, steps: usize) -> Option<&str> {
+        location(&self.back, steps)
+    }
+
+    pub fn forward_location(&self, steps: usize) -> Option<&str> {
+        location(&self.forward, steps)
+    }
+
+    pub fn go_back(&mut self, steps: usize) -> bool {
+        step(&mut self.back, &mut self.current, &mut self.forward, steps)
+    }
+
+    pub fn go_forward(&mut self, steps: usize) -> bool {
+        step(&mut self.forward, &mut self.current, &mut self.back, steps)
+    }
+
+    /// The back entries, the most recent first.
+    pub fn back_entries(&self) -> impl Iterator<Item = &str> {
+        self.back.iter().rev().map(String::as_str)
+    }
+
+    /// The forward entries, the nearest first.
+    pub fn forward_entries(&self) -> impl Iterator<Item = &str> {
+        self.forward.iter().rev().map(String::as_str)
+    }
+}
+
+fn location(entries: &[String], steps: usize) -> Option<&str> {
+    let index = entries.len().checked_sub(steps).filter(|_| steps > 0)?;
+    entries.get(index).map(String::as_str)
+}
+
+fn step(
+    from: &mut Vec<String>,
+    current: &mut Option<String>,
+    to: &mut Vec<String>,
+    steps: usize,
+) -> bool {
+    if location(from, steps).is_none() {
+        return false;
+    }
+    for _ in 0..steps {
+        if let Some(previous) = current.replace(from.pop().unwrap_or_default()) {
+            to.push(previous);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(locations: &[&str]) -> History {
+        let mut history = History::default();
+        for location in locations {
+            history.visit(location.to_string());
+        }
+        history
+    }
+
+    #[test]
+    fn goes_back_and_forward() {
+        let mut history = history(&["/", "/home", "/home/user"]);
+
+        assert_eq!(history.back_location(1), Some("/home"));
+        assert!(history.go_back(1));
+        assert_eq!(history.back_location(1), Some("/"));
+        assert_eq!(history.forward_location(1), Some("/home/user"));
+        assert!(history.go_forward(1));
+        assert_eq!(history.forward_location(1), None);
+        assert_eq!(history.back_location(1), Some("/home"));
+    }
+
+    #[test]
+    fn jumps_several_steps() {
+        let mut history = history(&["/a", "/b", "/c", "/d"]);
+
+        assert_eq!(history.back_location(3), Some("/a"));
+        assert!(history.go_back(3));
+        assert_eq!(
+            history.forward_entries().collect::<Vec<_>>(),
+            ["/b", "/c", "/d"]
+        );
+        assert!(history.back_entries().next().is_none());
+    }
+
+    #[test]
+    fn rejects_steps_beyond_history() {
+        let mut history = history(&["/a", "/b"]);
+
+        assert_eq!(history.back_location(0), None);
+        assert_eq!(history.back_location(2), None);
+        assert!(!history.go_back(2));
+        assert!(!history.go_forward(1));
+        assert_eq!(history.back_entries().collect::<Vec<_>>(), ["/a"]);
+    }
+
+    #[test]
+    fn visiting_drops_forward_entries() {
+        let mut history = history(&["/a", "/b", "/c"]);
+        history.go_back(2);
+        history.visit("/d".to_string());
+
+        assert_eq!(history.forward_location(1), None);
+        assert_eq!(history.back_entries().collect::<Vec<_>>(), ["/a"]);
+    }
+
+    #[test]
+    fn ignores_visiting_current_location() {
+        let history = history(&["/a", "/a", "/b", "/b"]);
+
+        assert_eq!(history.back_entries().collect::<Vec<_>>(), ["/a"]);
+    }
+
+    #[test]
+    fn limits_back_entries() {
+        let locations: Vec<String> = (0..=MAX_ENTRIES + 5).map(|i| format!("/{i}")).collect();
+        let mut history = History::default();
+        for location in locations {
+            history.visit(location);
+        }
+
+        assert_eq!(history.back_entries().count(), MAX_ENTRIES);
+        assert_eq!(history.back_entries().last(), Some("/5"));
+    }
+}
@@ -0,0 +1,520 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::create::{NewEntry, create};
+use super::delete::delete_tree;
+use super::journal::{Change, refusals};
+use super::rename::rename;
+use super::transfer::{TransferMode, TransferObserver, paste};
+use super::trash::trash;
+
+pub type JobId = usize;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub enum Operation {
+    Transfer(TransferMode, Vec<PathBuf>, PathBuf),
+    Trash(Vec<PathBuf>),
+    Delete(Vec<PathBuf>),
+    /// Gives each source the new name at the same index.
+    Rename(Vec<PathBuf>, Vec<String>),
+    Create(PathBuf, NewEntry),
+    /// Reverses each change, found at the path at the same index.
+    Undo(Vec<PathBuf>, Vec<Change>),
+}
+
+impl Operation {
+    pub fn description(&self) -> String {
+        match self {
+            Operation::Transfer(mode, sources, dest_dir) => {
+                let verb = match mode {
+                    TransferMode::Copy => "Copying",
+                    TransferMode::Move => "Moving",
+                };
+                format!("{verb} {} to {}", items(sources), display_name(dest_dir))
+            }
+            Operation::Trash(sources) => format!("Moving {} to the Trash", items(sources)),
+            Operation::Delete(sources) => format!("Deleting {}", items(sources)),
+            Operation::Rename(sources, _) => format!("Renaming {}", items(sources)),
+            Operation::Create(path, _) => format!("Creating “{}”", display_name(path)),
+            Operation::Undo(sources, _) => format!("Undoing changes to {}", items(sources)),
+        }
+    }
+
+    fn sources(&self) -> &[PathBuf] {
+        match self {
+            Operation::Transfer(_, sources, _)
+            | Operation::Trash(sources)
+            | Operation::Delete(sources)
+            | Operation::Rename(sources, _)
+            | Operation::Undo(sources, _) => sources,
+            Operation::Create(path, _) => std::slice::from_ref(path),
+        }
+    }
+
+    fn target(&self) -> &Path {
+        match self {
+            Operation::Transfer(_, _, dest_dir) => dest_dir,
+            _ => self
+                .sources()
+                .first()
+                .and_then(|source| source.parent())
+                .unwrap_or(Path::new("/")),
+        }
+    }
+
+    fn run(
+        &self,
+        source: &Path,
+        observer: &mut dyn TransferObserver,
+        errors: &mut Vec<String>,
+    ) -> io::Result<Option<Change>> {
+        let moved = |target: PathBuf| (target != source).then(|| Change::moved(source, &target));
+        match self {
+            Operation::Transfer(TransferMode::Copy, _, dest_dir) => {
+                let target = paste(source, dest_dir, TransferMode::Copy, observer, errors)?;
+                Ok(Some(Change::copied(&target)))
+            }
+            Operation::Transfer(TransferMode::Move, _, dest_dir) => {
+                paste(source, dest_dir, TransferMode::Move, observer, errors).map(moved)
+            }
+            Operation::Trash(_) => {
+                let trashed = trash(source)?;
+                Ok(Some(Change::Trashed(source.to_path_buf(), trashed)))
+            }
+            Operation::Delete(_) => delete_tree(source, observer, errors).map(|()| None),
+            Operation::Rename(sources, names) => {
+                let index = sources.iter().position(|known| known == source);
+                let name = index.and_then(|index| names.get(index));
+                rename(source, name.map_or("", String::as_str)).map(moved)
+            }
+            Operation::Create(_, entry) => {
+                create(source, entry)?;
+                Ok(Some(Change::created(source)))
+            }
+            Operation::Undo(sources, changes) => {
+                let index = sources.iter().position(|known| known == source);
+                let Some(change) = index.and_then(|index| changes.get(index)) else {
+                    return Ok(None);
+                };
+                change.revert(observer)?;
+                Ok(Some(change.clone()))
+            }
+        }
+    }
+
+    fn refusals(&self) -> Vec<String> {
+        match self {
+            Operation::Undo(_, changes) => refusals(changes),
+            _ => Vec::new(),
+        }
+    }
+
+    fn size(&self, source: &Path) -> (u64, u64) {
+        match self {
+            Operation::Transfer(..) | Operation::Delete(_) | Operation::Undo(..) => {
+                tree_size(source)
+            }
+            Operation::Trash(_) | Operation::Rename(..) | Operation::Create(..) => (0, 1),
+        }
+    }
+}
+
+fn items(sources: &[PathBuf]) -> String {
+    match sources {
+        [source] => format!("“{}”", display_name(source)),
+        sources => format!("{} items", sources.len()),
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.display().to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct JobProgress {
+    pub done_bytes: u64,
+    pub total_bytes: u64,
+    pub done_files: u64,
+    pub total_files: u64,
+    /// Bytes per second, leaving out the time spent paused.
+    pub throughput: f64,
+}
+
+impl JobProgress {
+    pub fn fraction(&self) -> f64 {
+        match (self.total_bytes, self.total_files) {
+            (0, 0) => 0.0,
+            (0, total_files) => self.done_files as f64 / total_files as f64,
+            (total_bytes, _) => self.done_bytes as f64 / total_bytes as f64,
+        }
+    }
+
+    /// The time left at the current throughput, once anything was copied.
+    pub fn remaining(&self) -> Option<Duration> {
+        let left = self.total_bytes.saturating_sub(self.done_bytes) as f64;
+        (self.throughput > 0.0).then(|| Duration::from_secs_f64(left / self.throughput))
+    }
+}
+
+#[derive(Debug)]
+pub enum JobEvent {
+    Queued(JobId, String),
+    Started(JobId),
+    Progress(JobId, JobProgress),
+    Paused(JobId, bool),
+    Finished(JobId, JobReport),
+}
+
+#[derive(Debug, Default)]
+pub struct JobReport {
+    pub errors: Vec<String>,
+    pub cancelled: bool,
+    /// The changes that can be undone, in the order they were made; for undoing,
+    /// the changes that were reversed.
+    pub changes: Vec<Change>,
+}
+
+type Notify = Arc<dyn Fn(JobEvent) + Send + Sync>;
+
+/// Runs the jobs of each device one after another, and different devices side by side.
+#[derive(Clone)]
+pub struct JobQueue {
+    state: Arc<Mutex<QueueState>>,
+    notify: Notify,
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_id: JobId,
+    devices: HashMap<u64, VecDeque<(JobId, Operation)>>,
+    controls: HashMap<JobId, Arc<JobControl>>,
+}
+
+impl JobQueue {
+    /// `notify` is called from the worker threads.
+    pub fn new(notify: impl Fn(JobEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            state: Arc::default(),
+            notify: Arc::new(notify),
+        }
+    }
+
+    pub fn start(&self, operation: Operation) -> JobId {
+        let device = device_id(operation.target());
+        let mut state = self.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.controls.insert(id, Arc::default());
+        (self.notify)(JobEvent::Queued(id, operation.description()));
+
+        match state.devices.get_mut(&device) {
+            Some(waiting) => waiting.push_back((id, operation)),
+            None => {
+                state
+                    .devices
+                    .insert(device, VecDeque::from([(id, operation)]));
+                let queue = self.clone();
+                thread::spawn(move || queue.run_worker(device));
+            }
+        }
+        id
+    }
+
+    pub fn pause(&self, id: JobId, paused: bool) {
+        if let Some(control) = self.lock().controls.get(&id) {
+            control.set_paused(paused);
+            (self.notify)(JobEvent::Paused(id, paused));
+        }
+    }
+
+    pub fn cancel(&self, id: JobId) {
+        if let Some(control) = self.lock().controls.get(&id) {
+            control.cancel();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn run_worker(&self, device: u64) {
+        loop {
+            let (id, operation, control) = {
+                let mut state = self.lock();
+                let next = state.devices.get_mut(&device).and_then(VecDeque::pop_front);
+                let Some((id, operation)) = next else {
+                    state.devices.remove(&device);
+                    return;
+                };
+                let control = state.controls.get(&id).cloned().unwrap_or_default();
+                (id, operation, control)
+            };
+
+            let mut report = self.run_job(id, &operation, &control);
+            report.cancelled = control.is_cancelled();
+            self.lock().controls.remove(&id);
+            (self.notify)(JobEvent::Finished(id, report));
+        }
+    }
+
+    fn run_job(&self, id: JobId, operation: &Operation, control: &JobControl) -> JobReport {
+        if control.checkpoint().is_err() {
+            return JobReport::default();
+        }
+        (self.notify)(JobEvent::Started(id));
+        let refusals = operation.refusals();
+        if !refusals.is_empty() {
+            return JobReport {
+                errors: refusals,
+                ..JobReport::default()
+            };
+        }
+
+        let sizes: Vec<(u64, u64)> = operation
+            .sources()
+            .iter()
+            .map(|source| operation.size(source))
+            .collect();
+        let mut runner = JobRunner {
+            id,
+            control,
+            notify: &self.notify,
+            progress: JobProgress {
+                total_bytes: sizes.iter().map(|(bytes, _)| bytes).sum(),
+                total_files: sizes.iter().map(|(_, files)| files).sum(),
+                ..JobProgress::default()
+            },
+            started: Instant::now(),
+            paused_for: Duration::ZERO,
+            reported: None,
+        };
+
+        let mut report = JobReport::default();
+        let (mut done_bytes, mut done_files) = (0, 0);
+        for (source, (bytes, files)) in operation.sources().iter().zip(sizes) {
+            match operation.run(source, &mut runner, &mut report.errors) {
+                Ok(change) => report.changes.extend(change),
+                Err(_) if control.is_cancelled() => break,
+                Err(error) => report.errors.push(format!("{}: {error}", source.display())),
+            }
+            done_bytes += bytes;
+            done_files += files;
+            runner.progress.done_bytes = done_bytes;
+            runner.progress.done_files = done_files;
+            runner.report(true);
+        }
+        report
+    }
+}
+
+#[derive(Default)]
+struct JobControl {
+    paused: Mutex<bool>,
+    resumed: Condvar,
+    cancelled: AtomicBool,
+}
+
+impl JobControl {
+    fn set_paused(&self, paused: bool) {
+        *self.paused.lock().unwrap_or_else(PoisonError::into_inner) = paused;
+        self.resumed.notify_all();
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        let _paused = self.paused.lock().unwrap_or_else(PoisonError::into_inner);
+        self.resumed.notify_all();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn checkpoint(&self) -> io::Result<Duration> {
+        let started = Instant::now();
+        let mut paused = self.paused.lock().unwrap_or_else(PoisonError::into_inner);
+        while *paused && !self.is_cancelled() {
+            paused = self
+                .resumed
+                .wait(paused)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        if self.is_cancelled() {
+            return Err(io::Error::other("Cancelled"));
+        }
+        Ok(started.elapsed())
+    }
+}
+
+struct JobRunner<'a> {
+    id: JobId,
+    control: &'a JobControl,
+    notify: &'a Notify,
+    progress: JobProgress,
+    started: Instant,
+    paused_for: Duration,
+    reported: Option<Instant>,
+}
+
+impl JobRunner<'_> {
+    fn checkpoint(&mut self) -> io::Result<()> {
+        self.paused_for += self.control.checkpoint()?;
+        self.report(false);
+        Ok(())
+    }
+
+    fn report(&mut self, now: bool) {
+        if !now
+            && self
+                .reported
+                .is_some_and(|at| at.elapsed() < PROGRESS_INTERVAL)
+        {
+            return;
+        }
+        self.reported = Some(Instant::now());
+        let active = self.started.elapsed().saturating_sub(self.paused_for);
+        if !active.is_zero() {
+            self.progress.throughput = self.progress.done_bytes as f64 / active.as_secs_f64();
+        }
+        (self.notify)(JobEvent::Progress(self.id, self.progress));
+    }
+}
+
+impl TransferObserver for JobRunner<'_> {
+    fn copied(&mut self, bytes: u64) -> io::Result<()> {
+        self.progress.done_bytes += bytes;
+        self.checkpoint()
+    }
+
+    fn file_copied(&mut self) -> io::Result<()> {
+        self.progress.done_files += 1;
+        self.checkpoint()
+    }
+}
+
+/// The bytes and the files (links included) below `path`.
+pub fn tree_size(path: &Path) -> (u64, u64) {
+    let Ok(metadata) = path.symlink_metadata() else {
+        return (0, 0);
+    };
+    if metadata.is_symlink() {
+        return (0, 1);
+    }
+    if !metadata.is_dir() {
+        return (metadata.len(), 1);
+    }
+    fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| tree_size(&entry.path()))
+        .fold((0, 0), |(bytes, files), (b, f)| (bytes + b, files + f))
+}
+
+#[cfg(unix)]
+fn device_id(path: &Path) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(path)
+        .map(|metadata| metadata.dev())
+        .unwrap_or_default()
+}
+
+#[cfg(windows)]
+fn device_id(path: &Path) -> u64 {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    path.components().next().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn runs_transfers_and_reports_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        fs::write(dir.path().join("src/a.txt"), b"abc").unwrap();
+        fs::write(dir.path().join("src/nested/b.txt"), b"de").unwrap();
+        fs::create_dir(dir.path().join("dest")).unwrap();
+
+        let (events, received) = mpsc::channel();
+        let queue = JobQueue::new(move |event| events.send(event).unwrap());
+        let id = queue.start(Operation::Transfer(
+            TransferMode::Copy,
+            vec![dir.path().join("src")],
+            dir.path().join("dest"),
+        ));
+
+        let events: Vec<JobEvent> = received
+            .iter()
+            .take_while(|event| !matches!(event, JobEvent::Finished(..)))
+            .collect();
+
+        assert!(matches!(&events[0], JobEvent::Queued(queued, description)
+            if *queued == id && description == "Copying “src” to dest"));
+        assert!(matches!(events[1], JobEvent::Started(started) if started == id));
+        let Some(JobEvent::Progress(_, progress)) = events.last() else {
+            panic!("no progress reported");
+        };
+        assert_eq!((progress.done_bytes, progress.total_bytes), (5, 5));
+        assert_eq!((progress.done_files, progress.total_files), (2, 2));
+        assert_eq!(progress.fraction(), 1.0);
+        assert_eq!(
+            fs::read(dir.path().join("dest/src/nested/b.txt")).unwrap(),
+            b"de"
+        );
+    }
+
+    #[test]
+    fn pauses_until_resumed_or_cancelled() {
+        let control = Arc::new(JobControl::default());
+        control.set_paused(true);
+
+        let waiting = thread::spawn({
+            let control = control.clone();
+            move || control.checkpoint()
+        });
+        thread::sleep(Duration::from_millis(20));
+        assert!(!waiting.is_finished());
+        control.set_paused(false);
+        assert!(waiting.join().unwrap().unwrap() >= Duration::from_millis(20));
+
+        control.set_paused(true);
+        let waiting = thread::spawn({
+            let control = control.clone();
+            move || control.checkpoint()
+        });
+        control.cancel();
+        assert!(waiting.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn estimates_the_time_left() {
+        let progress = JobProgress {
+            done_bytes: 25,
+            total_bytes: 100,
+            throughput: 5.0,
+            ..JobProgress::default()
+        };
+
+        assert_eq!(progress.fraction(), 0.25);
+        assert_eq!(progress.remaining(), Some(Duration::from_secs(15)));
+        assert_eq!(JobProgress::default().remaining(), None);
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::jobs::{JobId, Operation};
+use super::local_time::LocalTime;
+use super::transfer::{TransferObserver, move_to};
+use super::trash::{restore, trash, trashed_item, trashed_items};
+
+const MAX_ENTRIES: usize = 100;
+
+pub type EntryId = usize;
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.symlink_metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// What a job did to one entry, with the modification time it was left with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Moved(PathBuf, PathBuf, Option<SystemTime>),
+    Copied(PathBuf, Option<SystemTime>),
+    Created(PathBuf, Option<SystemTime>),
+    /// An entry moved from the first path to the trashed file at the second.
+    Trashed(PathBuf, PathBuf),
+}
+
+impl Change {
+    pub(super) fn moved(from: &Path, to: &Path) -> Self {
+        Change::Moved(from.to_path_buf(), to.to_path_buf(), modified(to))
+    }
+
+    pub(super) fn copied(path: &Path) -> Self {
+        Change::Copied(path.to_path_buf(), modified(path))
+    }
+
+    pub(super) fn created(path: &Path) -> Self {
+        Change::Created(path.to_path_buf(), modified(path))
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            Change::Moved(_, path, _)
+            | Change::Copied(path, _)
+            | Change::Created(path, _)
+            | Change::Trashed(_, path) => path,
+        }
+    }
+
+    fn check(&self, trashed: &[PathBuf]) -> Result<(), String> {
+        let unchanged = |path: &Path, stamp: &Option<SystemTime>| match modified(path) {
+            None => Err(format!("{} no longer exists", path.display())),
+            Some(time) if Some(time) != *stamp => {
+                Err(format!("{} was changed since", path.display()))
+            }
+            Some(_) => Ok(()),
+        };
+        let free = |path: &Path| match path.symlink_metadata() {
+            Ok(_) => Err(format!("{} exists again", path.display())),
+            Err(_) => Ok(()),
+        };
+
+        match self {
+            Change::Moved(from, to, stamp) => unchanged(to, stamp).and_then(|()| free(from)),
+            Change::Copied(path, stamp) | Change::Created(path, stamp) => unchanged(path, stamp),
+            Change::Trashed(original, path) if trashed.contains(path) => free(original),
+            Change::Trashed(original, _) => {
+                Err(format!("{} is no longer in the Trash", original.display()))
+            }
+        }
+    }
+
+    pub(super) fn revert(&self, observer: &mut dyn TransferObserver) -> io::Result<()> {
+        match self {
+            Change::Moved(from, to, _) => move_to(to, from, observer),
+            Change::Copied(path, _) | Change::Created(path, _) => trash(path).map(|_| ()),
+            Change::Trashed(original, path) => match trashed_item(original, path) {
+                Some(item) => restore(&item),
+                None => Err(io::Error::from(io::ErrorKind::NotFound)),
+            },
+        }
+    }
+}
+
+pub(super) fn refusals(changes: &[Change]) -> Vec<String> {
+    let trashed: Vec<PathBuf> = match changes.iter().any(|c| matches!(c, Change::Trashed(..))) {
+        true => trashed_items().into_iter().map(|item| item.path).collect(),
+        false => Vec::new(),
+    };
+    changes
+        .iter()
+        .filter_map(|change| change.check(&trashed).err())
+        .collect()
+}
+
+fn undo_operation(changes: &[Change]) -> Operation {
+    let changes: Vec<Change> = changes.iter().rev().cloned().collect();
+    let paths = changes
+        .iter()
+        .map(|change| change.path().to_path_buf())
+        .collect();
+    Operation::Undo(paths, changes)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Running,
+    Done,
+    Failed(usize),
+    Cancelled,
+    Undone,
+}
+
+impl Outcome {
+    pub fn title(&self) -> String {
+        match self {
+            Outcome::Running => "Running".to_owned(),
+            Outcome::Done => "Done".to_owned(),
+            Outcome::Failed(1) => "Failed with an error".to_owned(),
+            Outcome::Failed(errors) => format!("Failed with {errors} errors"),
+            Outcome::Cancelled => "Cancelled".to_owned(),
+            Outcome::Undone => "Undone".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    id: EntryId,
+    job: Option<JobId>,
+    pub operation: Operation,
+    pub time: LocalTime,
+    pub outcome: Outcome,
+    changes: Vec<Change>,
+}
+
+impl JournalEntry {
+    fn can_undo(&self) -> bool {
+        let finished = matches!(
+            self.outcome,
+            Outcome::Done | Outcome::Failed(_) | Outcome::Cancelled
+        );
+        finished && !self.changes.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Undoing {
+    entry: EntryId,
+    job: JobId,
+    /// Cleared when an operation starts meanwhile.
+    redoable: bool,
+}
+
+/// The recent file operations, which can be undone newest first and then redone.
+#[derive(Debug, Default)]
+pub struct Journal {
+    entries: VecDeque<JournalEntry>,
+    next_id: EntryId,
+    redo: Vec<EntryId>,
+    undoing: Option<Undoing>,
+}
+
+impl Journal {
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &JournalEntry> {
+        self.entries.iter()
+    }
+
+    /// What was undone before cannot be redone any more.
+    pub fn started(&mut self, job: JobId, operation: Operation) {
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(JournalEntry {
+            id: self.next_id,
+            job: Some(job),
+            operation,
+            time: LocalTime::now(),
+            outcome: Outcome::Running,
+            changes: Vec::new(),
+        });
+        self.next_id += 1;
+        self.redo.clear();
+        if let Some(undoing) = &mut self.undoing {
+            undoing.redoable = false;
+        }
+    }
+
+    pub fn finished(&mut self, job: JobId, changes: Vec<Change>, errors: usize, cancelled: bool) {
+        if let Some(undoing) = self.undoing.filter(|undoing| undoing.job == job) {
+            self.undone(undoing, &changes);
+            return;
+        }
+        let Some(entry) = self.entries.iter_mut().find(|entry| entry.job == Some(job)) else {
+            return;
+        };
+        entry.job = None;
+        entry.changes = changes;
+        entry.outcome = match (errors, cancelled) {
+            (_, true) => Outcome::Cancelled,
+            (0, false) => Outcome::Done,
+            (errors, false) => Outcome::Failed(errors),
+        };
+    }
+
+    fn undo_entry(&self) -> Option<&JournalEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.can_undo())
+            .filter(|_| self.undoing.is_none())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo_entry().is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty() && self.undoing.is_none()
+    }
+
+    pub fn undo(&mut self, start: impl FnOnce(Operation) -> JobId) {
+        let Some(entry) = self.undo_entry() else {
+            return;
+        };
+        let (entry, operation) = (entry.id, undo_operation(&entry.changes));
+        self.undoing = Some(Undoing {
+            entry,
+            job: start(operation),
+            redoable: true,
+        });
+    }
+
+    fn undone(&mut self, undoing: Undoing, reversed: &[Change]) {
+        self.undoing = None;
+        let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == undoing.entry)
+        else {
+            return;
+        };
+        entry.changes.retain(|change| !reversed.contains(change));
+        if entry.changes.is_empty() {
+            entry.outcome = Outcome::Undone;
+            if undoing.redoable {
+                self.redo.push(undoing.entry);
+            }
+        }
+    }
+
+    pub fn redo(&mut self, start: impl FnOnce(Operation) -> JobId) {
+        if !self.can_redo() {
+            return;
+        }
+        let Some(id) = self.redo.pop() else {
+            return;
+        };
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.job = Some(start(entry.operation.clone()));
+            entry.time = LocalTime::now();
+            entry.outcome = Outcome::Running;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::core::jobs::{JobEvent, JobQueue, JobReport};
+
+    fn rename(dir: &Path, from: &str, to: &str) -> Change {
+        fs::rename(dir.join(from), dir.join(to)).unwrap();
+        Change::moved(&dir.join(from), &dir.join(to))
+    }
+
+    fn run(operation: Operation) -> JobReport {
+        let (events, received) = mpsc::channel();
+        let queue = JobQueue::new(move |event| events.send(event).unwrap());
+        queue.start(operation);
+        received
+            .iter()
+            .find_map(|event| match event {
+                JobEvent::Finished(_, report) => Some(report),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn undoes_only_unchanged_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), "a").unwrap();
+        fs::write(dir.path().join("c"), "c").unwrap();
+        let changes = [rename(dir.path(), "a", "b"), rename(dir.path(), "c", "d")];
+
+        fs::write(dir.path().join("c"), "new c").unwrap();
+        let report = run(undo_operation(&changes));
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.changes.is_empty());
+        assert!(dir.path().join("b").exists());
+
+        fs::remove_file(dir.path().join("c")).unwrap();
+        let report = run(undo_operation(&changes));
+        assert!(report.errors.is_empty());
+        assert_eq!(report.changes, [changes[1].clone(), changes[0].clone()]);
+        assert_eq!(fs::read(dir.path().join("a")).unwrap(), b"a");
+        assert_eq!(fs::read(dir.path().join("c")).unwrap(), b"c");
+    }
+
+    #[test]
+    fn undoes_newest_first_and_redoes_undone() {
+        let mut journal = Journal::default();
+        let operation = |name: &str| Operation::Delete(vec![PathBuf::from(name)]);
+        let change = |name: &str| Change::Created(PathBuf::from(name), None);
+        let undone = |changes: &[Change]| {
+            let changes = changes.to_vec();
+            move |operation| {
+                assert!(matches!(operation, Operation::Undo(_, undone) if undone == changes));
+                10
+            }
+        };
+
+        journal.started(0, operation("first"));
+        journal.started(1, operation("second"));
+        journal.started(2, operation("nothing"));
+        assert!(!journal.can_undo());
+        journal.finished(0, vec![change("first")], 0, false);
+        journal.finished(1, vec![change("second")], 1, false);
+        journal.finished(2, Vec::new(), 0, false);
+
+        journal.undo(undone(&[change("second")]));
+        assert!(!journal.can_undo() && !journal.can_redo());
+        journal.finished(10, vec![change("second")], 0, false);
+        journal.undo(undone(&[change("first")]));
+        // Refused, nothing was reversed.
+        journal.finished(10, Vec::new(), 1, false);
+        let outcomes: Vec<Outcome> = journal.entries().map(|entry| entry.outcome).collect();
+        assert_eq!(outcomes, [Outcome::Done, Outcome::Undone, Outcome::Done]);
+
+        journal.redo(|operation| {
+            assert!(matches!(operation, Operation::Delete(paths) if paths[0].ends_with("second")));
+            3
+        });
+        assert!(!journal.can_redo());
+        journal.finished(3, vec![change("second")], 0, false);
+        journal.undo(undone(&[change("second")]));
+    }
+
+    #[test]
+    fn drops_redo_of_undo_overtaken_by_an_operation() {
+        let mut journal = Journal::default();
+        let change = Change::Created(PathBuf::from("first"), None);
+        journal.started(0, Operation::Delete(vec![PathBuf::from("first")]));
+        journal.finished(0, vec![change.clone()], 0, false);
+
+        journal.undo(|_| 10);
+        journal.started(1, Operation::Delete(vec![PathBuf::from("second")]));
+        journal.finished(10, vec![change], 0, false);
+        journal.finished(1, Vec::new(), 0, false);
+
+        assert!(!journal.can_redo());
+        let outcomes: Vec<Outcome> = journal.entries().map(|entry| entry.outcome).collect();
+        assert_eq!(outcomes, [Outcome::Undone, Outcome::Done]);
+    }
+}
@@ -0,0 +1,197 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A date and time of day in the local time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LocalTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl LocalTime {
+    pub fn now() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(error) => -(error.duration().as_secs() as i64),
+        };
+        Self::from_seconds(seconds + utc_offset(seconds))
+    }
+
+    pub fn to_system_time(self) -> SystemTime {
+        let local = self.seconds();
+        let seconds = local - utc_offset(local);
+        match u64::try_from(seconds) {
+            Ok(seconds) => UNIX_EPOCH + Duration::from_secs(seconds),
+            Err(_) => UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs()),
+        }
+    }
+
+    /// Reads `YYYY-MM-DDThh:mm:ss`, the format of `.trashinfo` files.
+    pub fn parse_iso(text: &str) -> Option<Self> {
+        let (date, time) = text.trim().split_once('T')?;
+        let mut date = date.splitn(3, '-');
+        let mut time = time.splitn(3, ':');
+        let year = date.next()?.parse().ok()?;
+        let (month, day) = (number(date.next())?, number(date.next())?);
+        let (hour, minute) = (number(time.next())?, number(time.next())?);
+        // Fractions of a second and time zones are allowed, and ignored.
+        let second = time.next()?.get(..2)?.parse().ok()?;
+
+        let valid = (1..=12).contains(&month)
+            && (1..=31).contains(&day)
+            && hour < 24
+            && minute < 60
+            && second < 61;
+        valid.then_some(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    pub fn format_iso(&self) -> String {
+        self.format("%Y-%m-%dT%H:%M:%S")
+    }
+
+    /// Formats the time with the `%Y`, `%m`, `%d`, `%H`, `%M` and `%S` fields of
+    /// `strftime`; other characters are kept.
+    pub fn format(&self, format: &str) -> String {
+        let mut text = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => text.push_str(&format!("{:04}", self.year)),
+                Some('m') => text.push_str(&format!("{:02}", self.month)),
+                Some('d') => text.push_str(&format!("{:02}", self.day)),
+                Some('H') => text.push_str(&format!("{:02}", self.hour)),
+                Some('M') => text.push_str(&format!("{:02}", self.minute)),
+                Some('S') => text.push_str(&format!("{:02}", self.second)),
+                Some('%') => text.push('%'),
+                Some(other) => {
+                    text.push('%');
+                    text.push(other);
+                }
+                None => text.push('%'),
+            }
+        }
+        text
+    }
+
+    /// The seconds since the epoch, reading the fields as UTC.
+    fn seconds(&self) -> i64 {
+        let (year, month) = match self.month {
+            1 | 2 => (self.year - 1, self.month as i64 + 9),
+            month => (self.year, month as i64 - 3),
+        };
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+        days * 86400 + (self.hour * 3600 + self.minute * 60 + self.second) as i64
+    }
+
+    fn from_seconds(seconds: i64) -> Self {
+        let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400) as u32);
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = match shifted_month {
+            0..10 => shifted_month + 3,
+            _ => shifted_month - 9,
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time % 3600 / 60,
+            second: time % 60,
+        }
+    }
+}
+
+fn number(text: Option<&str>) -> Option<u32> {
+    text?.parse().ok()
+}
+
+/// How far the local time zone is ahead of UTC at `seconds` past the epoch.
+#[cfg(unix)]
+fn utc_offset(seconds: i64) -> i64 {
+    let time = seconds as libc::time_t;
+    // SAFETY: `tm` is plain data and both pointers are valid for the call.
+    unsafe {
+        let mut tm = std::mem::zeroed::<libc::tm>();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            return 0;
+        }
+        tm.tm_gmtoff as i64
+    }
+}
+
+#[cfg(not(unix))]
+fn utc_offset(_: i64) -> i64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_dates_and_seconds() {
+        let time = LocalTime::parse_iso("2024-02-29T23:59:58").unwrap();
+
+        assert_eq!(time.seconds(), 1_709_251_198);
+        assert_eq!(LocalTime::from_seconds(1_709_251_198), time);
+        assert_eq!(
+            LocalTime::from_seconds(0).format_iso(),
+            "1970-01-01T00:00:00"
+        );
+        assert_eq!(
+            LocalTime::from_seconds(-1).format_iso(),
+            "1969-12-31T23:59:59"
+        );
+        assert_eq!(LocalTime::from_system_time(time.to_system_time()), time);
+    }
+
+    #[test]
+    fn parses_trash_info_dates() {
+        assert_eq!(
+            LocalTime::parse_iso("2004-08-31T22:32:08.123+02:00").map(|time| time.format_iso()),
+            Some("2004-08-31T22:32:08".to_owned())
+        );
+        assert_eq!(LocalTime::parse_iso("2004-13-31T22:32:08"), None);
+        assert_eq!(LocalTime::parse_iso("2004-08-31 22:32:08"), None);
+        assert_eq!(LocalTime::parse_iso("yesterday"), None);
+    }
+
+    #[test]
+    fn formats_strftime_fields() {
+        let time = LocalTime::parse_iso("2024-05-04T10:20:30").unwrap();
+
+        assert_eq!(time.format("%d.%m.%Y %H%M%S"), "04.05.2024 102030");
+        assert_eq!(time.format("100% %q"), "100% %q");
+    }
+}
@@ -0,0 +1,262 @@
+use std::env;
+use std::fs;
+use std::path::{Component, MAIN_SEPARATOR, Path, PathBuf};
+
+use super::{compare_names, home_dir};
+
+/// The result of completing a typed directory path.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Completion {
+    pub text: String,
+    pub candidates: Vec<String>,
+}
+
+/// Expands a leading `~` to the home directory and `$VAR`/`${VAR}` to environment
+/// variables; unknown variables are left as typed.
+pub fn expand_path(input: &str) -> String {
+    let home = home_dir().map(|home| home.display().to_string());
+    expand_with(input, home.as_deref(), |name| env::var(name).ok())
+}
+
+fn expand_with(input: &str, home: Option<&str>, var: impl Fn(&str) -> Option<String>) -> String {
+    let input = input.trim();
+    let mut expanded = String::with_capacity(input.len());
+    let mut rest = input;
+    if let Some(home) = home
+        && let Some(after) = rest.strip_prefix('~')
+        && (after.is_empty() || after.starts_with(['/', MAIN_SEPARATOR]))
+    {
+        expanded.push_str(home);
+        rest = after;
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remainder) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", after),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        match var(name).filter(|_| !name.is_empty()) {
+            Some(value) => expanded.push_str(&value),
+            None => expanded.push_str(&rest[start..rest.len() - remainder.len()]),
+        }
+        rest = remainder;
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Resolves `.` and `..` segments without following symlinks, the way a typed
+/// path reads; `..` stops at the root.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                Some(Component::ParentDir | Component::CurDir) | None => normalized.push(component),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// The breadcrumbs of a path as `(label, path)` pairs, starting with the root.
+pub fn path_segments(path: &str) -> Vec<(String, String)> {
+    let mut segments: Vec<(String, String)> = Path::new(path)
+        .ancestors()
+        .map(|ancestor| {
+            let label = ancestor
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| ancestor.display().to_string());
+            (label, ancestor.display().to_string())
+        })
+        .filter(|(label, _)| !label.is_empty())
+        .collect();
+    segments.reverse();
+    segments
+}
+
+/// The subdirectories of `dir` (following symlinks) in natural order.
+pub fn list_directories(dir: &Path, show_hidden: bool) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut directories: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().into_owned(),
+                entry.path(),
+            )
+        })
+        .filter(|(name, path)| (show_hidden || !name.starts_with('.')) && path.is_dir())
+        .collect();
+    directories.sort_by(|(a, _), (b, _)| compare_names(a, b, true, true));
+    directories.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Completes the last segment of a typed path to the matching directory names.
+/// Hidden directories are only offered once the segment starts with a dot.
+pub fn complete_directory(input: &str) -> Completion {
+    let unchanged = Completion {
+        text: input.to_owned(),
+        candidates: Vec::new(),
+    };
+    let Some(split) = input.rfind(['/', MAIN_SEPARATOR]) else {
+        return unchanged;
+    };
+    let (dir, prefix) = input.split_at(split + 1);
+
+    let names: Vec<String> = list_directories(Path::new(dir), prefix.starts_with('.'))
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+
+    match names.as_slice() {
+        [] => unchanged,
+        [name] => Completion {
+            text: format!("{dir}{name}{MAIN_SEPARATOR}"),
+            candidates: Vec::new(),
+        },
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |common, name| {
+                let length = common
+                    .char_indices()
+                    .zip(name.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map(|((index, c), _)| index + c.len_utf8())
+                    .unwrap_or(0);
+                &common[..length]
+            });
+            Completion {
+                text: format!("{dir}{common}"),
+                candidates: names.clone(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(input: &str) -> String {
+        expand_with(input, Some("/home/user"), |name| {
+            (name == "DATA").then(|| "/srv/data".to_owned())
+        })
+    }
+
+    #[test]
+    fn expands_home() {
+        assert_eq!(expand("~"), "/home/user");
+        assert_eq!(expand("~/Music"), "/home/user/Music");
+        assert_eq!(expand("~other/Music"), "~other/Music");
+        assert_eq!(expand("/tmp/~"), "/tmp/~");
+    }
+
+    #[test]
+    fn expands_variables() {
+        assert_eq!(expand("$DATA/photos"), "/srv/data/photos");
+        assert_eq!(expand("${DATA}photos"), "/srv/dataphotos");
+        assert_eq!(expand("/a/$MISSING/b"), "/a/$MISSING/b");
+        assert_eq!(expand("/a/${DATA"), "/a/${DATA");
+        assert_eq!(expand("  /tmp/$ "), "/tmp/$");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn splits_path_into_segments() {
+        assert_eq!(
+            path_segments("/home/user"),
+            [
+                ("/".to_owned(), "/".to_owned()),
+                ("home".to_owned(), "/home".to_owned()),
+                ("user".to_owned(), "/home/user".to_owned()),
+            ]
+        );
+        assert_eq!(path_segments("/"), [("/".to_owned(), "/".to_owned())]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_parent_segments() {
+        let normalize = |path: &str| normalize_path(Path::new(path)).display().to_string();
+        assert_eq!(normalize("/home/user/.."), "/home");
+        assert_eq!(normalize("/home/user/../other/./docs"), "/home/other/docs");
+        assert_eq!(normalize("/home/../../.."), "/");
+        assert_eq!(normalize("../a/../b"), "../b");
+
+        let typed = Path::new("/home/user").join(expand("~/../x"));
+        assert_eq!(
+            path_segments(&normalize(&typed.display().to_string())),
+            [
+                ("/".to_owned(), "/".to_owned()),
+                ("home".to_owned(), "/home".to_owned()),
+                ("x".to_owned(), "/home/x".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_directories_naturally() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["dir10", "dir2", ".hidden"] {
+            fs::create_dir(dir.path().join(name)).unwrap();
+        }
+        fs::write(dir.path().join("file"), b"").unwrap();
+
+        let names = |show_hidden| -> Vec<String> {
+            list_directories(dir.path(), show_hidden)
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        assert_eq!(names(false), ["dir2", "dir10"]);
+        assert_eq!(names(true), [".hidden", "dir2", "dir10"]);
+    }
+
+    #[test]
+    fn completes_directory_names() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["Documents", "Downloads", "Music", ".config"] {
+            fs::create_dir(dir.path().join(name)).unwrap();
+        }
+        fs::write(dir.path().join("Dockerfile"), b"").unwrap();
+        let base = format!("{}{MAIN_SEPARATOR}", dir.path().display());
+
+        let unique = complete_directory(&format!("{base}Mu"));
+        assert_eq!(unique.text, format!("{base}Music{MAIN_SEPARATOR}"));
+        assert!(unique.candidates.is_empty());
+
+        let ambiguous = complete_directory(&format!("{base}D"));
+        assert_eq!(ambiguous.text, format!("{base}Do"));
+        assert_eq!(ambiguous.candidates, ["Documents", "Downloads"]);
+
+        let hidden = complete_directory(&format!("{base}.c"));
+        assert_eq!(hidden.text, format!("{base}.config{MAIN_SEPARATOR}"));
+
+        let missing = complete_directory(&format!("{base}X"));
+        assert_eq!(missing.text, format!("{base}X"));
+        assert!(missing.candidates.is_empty());
+    }
+}
@@ -1,17 +1,179 @@
+mod bookmarks;
+mod clipboard;
+mod command_line;
+mod create;
+mod delete;
+mod entry;
+mod history;
+mod jobs;
+mod journal;
+mod local_time;
+mod location;
+mod rename;
+mod sort;
+mod thumbnailer;
+mod transfer;
+mod trash;
+mod uri;
+mod user_dirs;
+mod users;
+
 use anyhow::{Context, Result};
-use std::fs;
-use std::path::PathBuf;
-
-pub fn load_items(current_dir: &str) -> Result<Vec<PathBuf>> {
-    let mut dir = fs::read_dir(current_dir).context("Error while open directory")?;
-    let mut items = Vec::new();
-    while let Some(Ok(item)) = dir.next() {
-        items.push(item.path());
+use tokio::fs;
+
+pub use bookmarks::{Bookmark, bookmarks_file, load_bookmarks, save_bookmarks};
+pub use clipboard::{
+    GNOME_COPIED_FILES, URI_LIST, format_gnome_copied_files, format_plain_paths, format_uri_list,
+    parse_gnome_copied_files, parse_uri_list,
+};
+pub use command_line::CommandLine;
+pub use create::{NewEntry, templates};
+pub use entry::{DirEntryInfo, EntryType};
+pub use history::History;
+pub use jobs::{JobEvent, JobId, JobProgress, JobQueue, Operation, tree_size};
+pub use journal::{Journal, JournalEntry, Outcome};
+pub use local_time::LocalTime;
+pub use location::{
+    Completion, complete_directory, expand_path, list_directories, normalize_path, path_segments,
+};
+pub use rename::{Case, RenameConflict, RenameItem, RenamePattern, rename_conflicts};
+pub use sort::{compare_names, extension};
+pub use thumbnailer::{Thumbnailer, load_thumbnailers, thumbnail_cache_dir};
+pub use transfer::TransferMode;
+pub use trash::{
+    TRASH_URI, TrashedItem, delete_trashed, empty_trash, home_trash, restore, trashed_items,
+};
+pub use user_dirs::{UserDir, config_dir, data_dir, home_dir, user_dirs};
+pub use users::user_name;
+
+pub struct ItemsReader {
+    dir: fs::ReadDir,
+    chunk_size: usize,
+}
+
+impl ItemsReader {
+    pub async fn open(current_dir: &str, chunk_size: usize) -> Result<Self> {
+        let dir = fs::read_dir(current_dir)
+            .await
+            .context("Error while open directory")?;
+        Ok(Self { dir, chunk_size })
     }
 
-    Ok(items)
+    /// The next entries of the directory, `None` once all were read. An error cuts the
+    /// listing short rather than leaving it looking complete; entries removed while
+    /// being read are skipped.
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<DirEntryInfo>>> {
+        let mut paths = Vec::with_capacity(self.chunk_size);
+        while paths.len() < self.chunk_size {
+            match self
+                .dir
+                .next_entry()
+                .await
+                .context("Error while reading directory")?
+            {
+                Some(item) => paths.push(item.path()),
+                None => break,
+            }
+        }
+
+        if paths.is_empty() {
+            return Ok(None);
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let mut entries = Vec::with_capacity(paths.len());
+            for path in &paths {
+                match DirEntryInfo::read(path) {
+                    Ok(entry) => entries.push(entry),
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(error) => {
+                        return Err(error)
+                            .with_context(|| format!("Error while reading {}", path.display()));
+                    }
+                }
+            }
+            Ok(Some(entries))
+        })
+        .await
+        .context("Error while reading directory")?
+    }
 }
 
 pub fn open_item(path: &str) -> Result<()> {
-    open::that(&path).context("Error while opening the file")
+    open::that(path).context("Error while opening the file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_directory_in_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a", "b", "c", "d", "e"] {
+            std::fs::write(dir.path().join(name), name).unwrap();
+        }
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+
+        let mut reader = ItemsReader::open(dir.path().to_str().unwrap(), 4)
+            .await
+            .unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = reader.next_chunk().await.unwrap() {
+            chunks.push(chunk);
+        }
+
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![4, 2]);
+        let mut names: Vec<_> = chunks
+            .iter()
+            .flatten()
+            .map(|entry| entry.name().to_os_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a", "b", "c", "d", "e", "nested"]);
+        assert_eq!(
+            chunks
+                .iter()
+                .flatten()
+                .filter(|entry| entry.is_dir())
+                .count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_entries_removed_while_reading() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a", "b", "c"] {
+            std::fs::write(dir.path().join(name), name).unwrap();
+        }
+
+        let mut reader = ItemsReader::open(dir.path().to_str().unwrap(), 8)
+            .await
+            .unwrap();
+        std::fs::remove_file(dir.path().join("b")).unwrap();
+        let mut names: Vec<_> = reader
+            .next_chunk()
+            .await
+            .unwrap()
+            .unwrap()
+            .iter()
+            .map(|entry| entry.name().to_os_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a", "c"]);
+        assert!(reader.next_chunk().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn fails_to_open_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+
+        assert!(
+            ItemsReader::open(missing.to_str().unwrap(), 4)
+                .await
+                .is_err()
+        );
+    }
 }
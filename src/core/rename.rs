@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::extension;
+use super::local_time::LocalTime;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The case conversion of a batch rename, applied to the `{name}` token.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Case {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    Title,
+}
+
+impl Case {
+    pub const ALL: [Case; 4] = [Case::Keep, Case::Lower, Case::Upper, Case::Title];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Case::Keep => "Keep Case",
+            Case::Lower => "lowercase",
+            Case::Upper => "UPPERCASE",
+            Case::Title => "Title Case",
+        }
+    }
+
+    fn convert(&self, text: &str) -> String {
+        match self {
+            Case::Keep => text.to_owned(),
+            Case::Lower => text.to_lowercase(),
+            Case::Upper => text.to_uppercase(),
+            Case::Title => {
+                let mut word_start = true;
+                text.chars()
+                    .flat_map(|c| {
+                        let converted: Vec<char> = match word_start {
+                            true => c.to_uppercase().collect(),
+                            false => c.to_lowercase().collect(),
+                        };
+                        word_start = !c.is_alphanumeric();
+                        converted
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// An item of a batch rename, after find and replace.
+pub struct RenameItem<'a> {
+    pub name: &'a str,
+    pub is_dir: bool,
+    pub modified: Option<LocalTime>,
+}
+
+/// How a batch rename builds the new names. The template may contain the tokens
+/// `{name}` and `{ext}` (the name without and with only its extension),
+/// `{n}` or `{n:3}` (a counter, padded with zeros to the given width), and
+/// `{date}` or `{today}` (the modification date or the current one, formatted
+/// like `{date:%Y%m%d}`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenamePattern {
+    pub template: String,
+    pub case: Case,
+    /// The counter of the first item.
+    pub start: u64,
+}
+
+impl Default for RenamePattern {
+    fn default() -> Self {
+        Self {
+            template: "{name}{ext}".to_owned(),
+            case: Case::Keep,
+            start: 1,
+        }
+    }
+}
+
+impl RenamePattern {
+    /// The new name of the `index`-th item.
+    pub fn new_name(&self, item: &RenameItem, index: usize, today: LocalTime) -> String {
+        let extension = match item.is_dir {
+            true => "",
+            false => extension(item.name),
+        };
+        let stem = match extension {
+            "" => item.name,
+            extension => &item.name[..item.name.len() - extension.len() - 1],
+        };
+
+        let mut name = String::new();
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            name.push_str(&rest[..start]);
+            let Some(length) = rest[start..].find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            let token = &rest[start + 1..start + length];
+            let (key, argument) = match token.split_once(':') {
+                Some((key, argument)) => (key, Some(argument)),
+                None => (token, None),
+            };
+            match (key, argument) {
+                ("name", None) => name.push_str(&self.case.convert(stem)),
+                ("ext", None) if !extension.is_empty() => {
+                    name.push('.');
+                    name.push_str(extension);
+                }
+                ("ext", None) => {}
+                ("n", width) => {
+                    let width = width.and_then(|width| width.parse().ok()).unwrap_or(0);
+                    let number = self.start + index as u64;
+                    name.push_str(&format!("{number:0width$}"));
+                }
+                ("date", format) => {
+                    if let Some(modified) = item.modified {
+                        name.push_str(&modified.format(format.unwrap_or(DATE_FORMAT)));
+                    }
+                }
+                ("today", format) => name.push_str(&today.format(format.unwrap_or(DATE_FORMAT))),
+                // Unknown tokens stay as they are.
+                _ => name.push_str(&rest[start..=start + length]),
+            }
+            rest = &rest[start + length + 1..];
+        }
+        name.push_str(rest);
+        name
+    }
+}
+
+/// Why an item cannot get its new name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameConflict {
+    Invalid,
+    /// Another item of the rename gets the same name.
+    Duplicate,
+    /// An entry with the name exists already.
+    Exists,
+}
+
+impl RenameConflict {
+    pub fn description(&self) -> &'static str {
+        match self {
+            RenameConflict::Invalid => "Invalid name",
+            RenameConflict::Duplicate => "Same name as another item",
+            RenameConflict::Exists => "Already exists",
+        }
+    }
+}
+
+/// The conflicts of renaming each source to its new name; items that keep their
+/// name have none.
+pub fn rename_conflicts(renames: &[(PathBuf, String)]) -> Vec<Option<RenameConflict>> {
+    let targets: Vec<PathBuf> = renames
+        .iter()
+        .map(|(source, name)| source.with_file_name(name))
+        .collect();
+    let mut counts = HashMap::new();
+    for target in &targets {
+        *counts.entry(target).or_insert(0) += 1;
+    }
+
+    renames
+        .iter()
+        .zip(&targets)
+        .map(|((source, name), target)| {
+            if !valid_name(name) {
+                Some(RenameConflict::Invalid)
+            } else if counts[target] > 1 {
+                Some(RenameConflict::Duplicate)
+            } else if target != source && target.symlink_metadata().is_ok() {
+                Some(RenameConflict::Exists)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Gives `source` a new name in the same folder, never replacing another entry.
+pub fn rename(source: &Path, name: &str) -> io::Result<PathBuf> {
+    if !valid_name(name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("“{name}” is not a valid name"),
+        ));
+    }
+    let target = source.with_file_name(name);
+    if target == source {
+        return Ok(target);
+    }
+    if target.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", target.display()),
+        ));
+    }
+    fs::rename(source, &target)?;
+    Ok(target)
+}
+
+fn valid_name(name: &str) -> bool {
+    !matches!(name, "" | "." | "..") && !name.contains(['/', '\0'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str) -> RenameItem<'_> {
+        RenameItem {
+            name,
+            is_dir: false,
+            modified: LocalTime::parse_iso("2024-05-04T10:20:30"),
+        }
+    }
+
+    #[test]
+    fn builds_names_from_templates() {
+        let today = LocalTime::parse_iso("2025-01-02T03:04:05").unwrap();
+        let pattern = |template: &str, case| RenamePattern {
+            template: template.to_owned(),
+            case,
+            start: 7,
+        };
+
+        let name =
+            |template, case| pattern(template, case).new_name(&item("my photo.JPG"), 2, today);
+        assert_eq!(name("{name}{ext}", Case::Keep), "my photo.JPG");
+        assert_eq!(name("{name}{ext}", Case::Title), "My Photo.JPG");
+        assert_eq!(name("{name}{ext}", Case::Upper), "MY PHOTO.JPG");
+        assert_eq!(name("{n:3} {name}{ext}", Case::Lower), "009 my photo.JPG");
+        assert_eq!(name("{date}_{n}{ext}", Case::Keep), "2024-05-04_9.JPG");
+        assert_eq!(
+            name("{today:%Y%m%d} {unknown}{ext", Case::Keep),
+            "20250102 {unknown}{ext"
+        );
+
+        let folder = RenameItem {
+            name: "v1.2",
+            is_dir: true,
+            modified: None,
+        };
+        assert_eq!(
+            pattern("{name}-{date}{ext}", Case::Keep).new_name(&folder, 0, today),
+            "v1.2-"
+        );
+    }
+
+    #[test]
+    fn finds_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a", "b", "taken"] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        let rename_to = |from: &str, to: &str| (dir.path().join(from), to.to_owned());
+
+        let conflicts = rename_conflicts(&[
+            rename_to("a", "same"),
+            rename_to("b", "same"),
+            rename_to("c", "b"),
+            rename_to("d", "x/y"),
+            rename_to("taken", "taken"),
+        ]);
+
+        assert_eq!(
+            conflicts,
+            [
+                Some(RenameConflict::Duplicate),
+                Some(RenameConflict::Duplicate),
+                Some(RenameConflict::Exists),
+                Some(RenameConflict::Invalid),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn renames_without_replacing() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), "a").unwrap();
+        fs::write(dir.path().join("b"), "b").unwrap();
+
+        assert!(rename(&dir.path().join("a"), "b").is_err());
+        assert!(rename(&dir.path().join("a"), "").is_err());
+        assert_eq!(
+            rename(&dir.path().join("a"), "c").unwrap(),
+            dir.path().join("c")
+        );
+        assert_eq!(fs::read(dir.path().join("c")).unwrap(), b"a");
+    }
+}
@@ -0,0 +1,147 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Compares file names, optionally treating digit runs as numbers (`file2` < `file10`)
+/// and ignoring case. Names equal under these rules fall back to a byte-wise comparison,
+/// so the order is always total.
+pub fn compare_names(a: &str, b: &str, natural: bool, case_insensitive: bool) -> Ordering {
+    let ordering = match (natural, case_insensitive) {
+        (true, _) => natural_cmp(a, b, case_insensitive),
+        (false, true) => a
+            .chars()
+            .flat_map(char::to_lowercase)
+            .cmp(b.chars().flat_map(char::to_lowercase)),
+        (false, false) => Ordering::Equal,
+    };
+
+    ordering.then_with(|| a.cmp(b))
+}
+
+/// The extension of a file name without the dot; dot files such as `.bashrc` have none.
+pub fn extension(name: &str) -> &str {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => extension,
+        _ => "",
+    }
+}
+
+fn natural_cmp(a: &str, b: &str, case_insensitive: bool) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ordering = match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                compare_numbers(&take_digits(&mut a), &take_digits(&mut b))
+            }
+            (Some(x), Some(y)) => {
+                a.next();
+                b.next();
+                if case_insensitive {
+                    x.to_lowercase().cmp(y.to_lowercase())
+                } else {
+                    x.cmp(&y)
+                }
+            }
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn take_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+/// Compares digit runs by value without parsing, so arbitrarily long numbers work;
+/// on equal values the run with fewer leading zeros goes first.
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let (a_value, b_value) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+    a_value
+        .len()
+        .cmp(&b_value.len())
+        .then_with(|| a_value.cmp(b_value))
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str], natural: bool, case_insensitive: bool) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        names.sort_by(|a, b| compare_names(a, b, natural, case_insensitive));
+        names
+    }
+
+    #[test]
+    fn sorts_numbers_naturally() {
+        assert_eq!(
+            sorted(&["file10", "file2", "file1", "file02"], true, false),
+            ["file1", "file2", "file02", "file10"]
+        );
+    }
+
+    #[test]
+    fn sorts_byte_wise_without_natural_order() {
+        assert_eq!(
+            sorted(&["file10", "file2", "file1"], false, false),
+            ["file1", "file10", "file2"]
+        );
+    }
+
+    #[test]
+    fn ignores_case() {
+        assert_eq!(
+            sorted(&["Zeta", "alpha", "Beta"], false, true),
+            ["alpha", "Beta", "Zeta"]
+        );
+        assert_eq!(
+            sorted(&["Zeta", "alpha", "Beta"], false, false),
+            ["Beta", "Zeta", "alpha"]
+        );
+    }
+
+    #[test]
+    fn combines_natural_order_and_case() {
+        assert_eq!(
+            sorted(&["IMG_10.jpg", "img_9.jpg", "Img_100.jpg"], true, true),
+            ["img_9.jpg", "IMG_10.jpg", "Img_100.jpg"]
+        );
+    }
+
+    #[test]
+    fn breaks_ties_deterministically() {
+        assert_eq!(compare_names("a", "A", true, true), "a".cmp("A"));
+        assert_eq!(compare_names("same", "same", true, true), Ordering::Equal);
+    }
+
+    #[test]
+    fn handles_huge_numbers() {
+        assert_eq!(
+            compare_names(
+                "v99999999999999999999999",
+                "v100000000000000000000000",
+                true,
+                false
+            ),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn extracts_extension() {
+        assert_eq!(extension("archive.tar.gz"), "gz");
+        assert_eq!(extension("README"), "");
+        assert_eq!(extension(".bashrc"), "");
+        assert_eq!(extension(".config.toml"), "toml");
+    }
+}
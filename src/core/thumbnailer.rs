@@ -0,0 +1,266 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{data_dir, home_dir};
+
+/// An external thumbnailer described by a freedesktop `.thumbnailer` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnailer {
+    _try_exec: Option<String>,
+    _exec: Vec<String>,
+    _mime_types: Vec<String>,
+}
+
+impl Thumbnailer {
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut in_entry = false;
+        let (mut try_exec, mut exec, mut mime_types) = (None, None, Vec::new());
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_entry = line == "[Thumbnailer Entry]";
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=').filter(|_| in_entry) else {
+                continue;
+            };
+            match key.trim() {
+                "TryExec" => try_exec = Some(value.trim().to_owned()),
+                "Exec" => exec = Some(split_exec(value.trim())),
+                "MimeType" => {
+                    mime_types = value
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|mime_type| !mime_type.is_empty())
+                        .map(str::to_owned)
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        let exec = exec.filter(|exec| !exec.is_empty())?;
+        Some(Self {
+            _try_exec: try_exec,
+            _exec: exec,
+            _mime_types: mime_types,
+        })
+    }
+
+    pub fn mime_types(&self) -> &[String] {
+        &self._mime_types
+    }
+
+    /// Whether the program of the thumbnailer (or its `TryExec`) is installed.
+    pub fn is_available(&self) -> bool {
+        let program = self._try_exec.as_deref().unwrap_or(&self._exec[0]);
+        find_program(program).is_some()
+    }
+
+    /// The command line with `%i`, `%u`, `%o`, `%s` and `%%` expanded.
+    pub fn command(&self, input: &Path, uri: &str, output: &Path, size: u32) -> Vec<OsString> {
+        self._exec
+            .iter()
+            .map(|argument| match argument.as_str() {
+                "%i" => input.as_os_str().to_os_string(),
+                "%o" => output.as_os_str().to_os_string(),
+                _ => expand(argument, input, uri, output, size).into(),
+            })
+            .collect()
+    }
+}
+
+/// All available thumbnailers from the `thumbnailers` directories of the XDG data dirs,
+/// with earlier directories taking precedence.
+pub fn load_thumbnailers() -> Vec<Thumbnailer> {
+    let data_home = data_dir();
+    let data_dirs = env::var_os("XDG_DATA_DIRS")
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+    let mut names = Vec::new();
+    let mut thumbnailers = Vec::new();
+    for dir in data_home.into_iter().chain(env::split_paths(&data_dirs)) {
+        let Ok(entries) = fs::read_dir(dir.join("thumbnailers")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "thumbnailer")
+                || names.contains(&entry.file_name())
+            {
+                continue;
+            }
+            names.push(entry.file_name());
+            let thumbnailer = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| Thumbnailer::parse(&content));
+            if let Some(thumbnailer) = thumbnailer.filter(Thumbnailer::is_available) {
+                thumbnailers.push(thumbnailer);
+            }
+        }
+    }
+    thumbnailers
+}
+
+/// The shared thumbnail cache, `$XDG_CACHE_HOME/thumbnails` or `~/.cache/thumbnails`.
+pub fn thumbnail_cache_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".cache")))
+        .map(|cache| cache.join("thumbnails"))
+}
+
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut argument = String::new();
+    let (mut quoted, mut started) = (false, false);
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            '\\' if quoted => argument.extend(chars.next()),
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    arguments.push(std::mem::take(&mut argument));
+                    started = false;
+                }
+            }
+            c => {
+                argument.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        arguments.push(argument);
+    }
+    arguments
+}
+
+fn expand(argument: &str, input: &Path, uri: &str, output: &Path, size: u32) -> String {
+    let mut expanded = String::with_capacity(argument.len());
+    let mut chars = argument.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('i') => expanded.push_str(&input.to_string_lossy()),
+            Some('u') => expanded.push_str(uri),
+            Some('o') => expanded.push_str(&output.to_string_lossy()),
+            Some('s') => expanded.push_str(&size.to_string()),
+            Some('%') => expanded.push('%'),
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+    expanded
+}
+
+fn find_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.is_absolute() {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVINCE: &str = "\
+[Thumbnailer Entry]
+TryExec=/bin/sh
+Exec=evince-thumbnailer -s %s %u %o
+MimeType=application/pdf;application/x-bzpdf;
+";
+
+    #[test]
+    fn parses_thumbnailer_entry() {
+        let thumbnailer = Thumbnailer::parse(EVINCE).unwrap();
+
+        assert_eq!(
+            thumbnailer.mime_types(),
+            ["application/pdf", "application/x-bzpdf"]
+        );
+        assert!(thumbnailer.is_available());
+    }
+
+    #[test]
+    fn ignores_entries_without_exec() {
+        assert_eq!(
+            Thumbnailer::parse("[Thumbnailer Entry]\nMimeType=image/png;\n"),
+            None
+        );
+        assert_eq!(
+            Thumbnailer::parse("[Desktop Entry]\nExec=thumbnail %i %o\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn expands_command_line() {
+        let thumbnailer = Thumbnailer::parse(
+            "[Thumbnailer Entry]\nExec=thumb --size=%s \"%u\" %o 100%%\nMimeType=a/b;\n",
+        )
+        .unwrap();
+
+        let command = thumbnailer.command(
+            Path::new("/home/user/a b.pdf"),
+            "file:///home/user/a%20b.pdf",
+            Path::new("/tmp/out.png"),
+            128,
+        );
+
+        assert_eq!(
+            command,
+            [
+                "thumb",
+                "--size=128",
+                "file:///home/user/a%20b.pdf",
+                "/tmp/out.png",
+                "100%"
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_input_path_intact() {
+        let thumbnailer =
+            Thumbnailer::parse("[Thumbnailer Entry]\nExec=thumb %i %o\nMimeType=a/b;\n").unwrap();
+
+        let command = thumbnailer.command(
+            Path::new("/data/photo 1.jpg"),
+            "",
+            Path::new("/tmp/out.png"),
+            256,
+        );
+
+        assert_eq!(command[1], "/data/photo 1.jpg");
+    }
+
+    #[test]
+    fn detects_missing_program() {
+        let thumbnailer = Thumbnailer::parse(
+            "[Thumbnailer Entry]\nExec=/nonexistent/thumbnailer %i %o\nMimeType=a/b;\n",
+        )
+        .unwrap();
+
+        assert!(!thumbnailer.is_available());
+    }
+}
@@ -0,0 +1,485 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+const COPY_CHUNK_SIZE: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+/// Follows a transfer or a deletion as it goes; returning an error stops it.
+pub trait TransferObserver {
+    /// Called after each chunk written to a file, or with the size of a deleted file.
+    fn copied(&mut self, bytes: u64) -> io::Result<()>;
+    /// Called after each file or link is copied or deleted.
+    fn file_copied(&mut self) -> io::Result<()>;
+}
+
+impl TransferObserver for () {
+    fn copied(&mut self, _: u64) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn file_copied(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Never overwrites; sockets and devices skipped in a copied folder go to `errors`.
+pub fn transfer(
+    source: &Path,
+    dest_dir: &Path,
+    mode: TransferMode,
+    observer: &mut dyn TransferObserver,
+    errors: &mut Vec<String>,
+) -> io::Result<PathBuf> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Cannot transfer a root"))?;
+    if dest_dir.starts_with(source) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot transfer a folder into itself",
+        ));
+    }
+    let target = dest_dir.join(name);
+    if target.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", target.display()),
+        ));
+    }
+    transfer_to(source, &target, mode, observer, errors)?;
+    Ok(target)
+}
+
+/// Like `transfer`, but copies into the folder of `source` under a "name (copy)" name.
+pub fn paste(
+    source: &Path,
+    dest_dir: &Path,
+    mode: TransferMode,
+    observer: &mut dyn TransferObserver,
+    errors: &mut Vec<String>,
+) -> io::Result<PathBuf> {
+    if source.parent() != Some(dest_dir) {
+        return transfer(source, dest_dir, mode, observer, errors);
+    }
+    match mode {
+        TransferMode::Copy => {
+            let target = copy_target(source)?;
+            copy_recursive(source, &target, observer, errors)?;
+            Ok(target)
+        }
+        TransferMode::Move => Ok(source.to_path_buf()),
+    }
+}
+
+fn copy_target(source: &Path) -> io::Result<PathBuf> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Cannot copy a root"))?;
+    let extension = match source.is_dir() {
+        true => None,
+        false => source.extension().filter(|extension| !extension.is_empty()),
+    };
+    let stem = match extension {
+        Some(_) => source.file_stem().unwrap_or(name),
+        None => name,
+    };
+
+    (1..)
+        .map(|index| {
+            let mut copy = stem.to_os_string();
+            match index {
+                1 => copy.push(" (copy)"),
+                index => copy.push(format!(" (copy {index})")),
+            }
+            if let Some(extension) = extension {
+                copy.push(".");
+                copy.push(extension);
+            }
+            source.with_file_name(copy)
+        })
+        .find(|target| target.symlink_metadata().is_err())
+        .ok_or_else(|| io::Error::other("No free name left"))
+}
+
+pub(super) fn move_to(
+    source: &Path,
+    target: &Path,
+    observer: &mut dyn TransferObserver,
+) -> io::Result<()> {
+    transfer_to(
+        source,
+        target,
+        TransferMode::Move,
+        observer,
+        &mut Vec::new(),
+    )
+}
+
+fn transfer_to(
+    source: &Path,
+    target: &Path,
+    mode: TransferMode,
+    observer: &mut dyn TransferObserver,
+    errors: &mut Vec<String>,
+) -> io::Result<()> {
+    match mode {
+        TransferMode::Copy => copy_recursive(source, target, observer, errors),
+        TransferMode::Move => match fs::rename(source, target) {
+            Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+                let mut skipped = Vec::new();
+                copy_recursive(source, target, observer, &mut skipped)?;
+                match skipped.first() {
+                    Some(error) => Err(io::Error::other(format!(
+                        "{error}, so {} was copied but kept",
+                        source.display()
+                    ))),
+                    None => remove_recursive(source),
+                }
+            }
+            result => result,
+        },
+    }
+}
+
+fn copy_recursive(
+    source: &Path,
+    target: &Path,
+    observer: &mut dyn TransferObserver,
+    errors: &mut Vec<String>,
+) -> io::Result<()> {
+    let metadata = source.symlink_metadata()?;
+    if metadata.is_symlink() {
+        copy_symlink(source, target)?;
+    } else if metadata.is_dir() {
+        fs::create_dir(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            let (source, target) = (entry.path(), target.join(entry.file_name()));
+            match copy_recursive(&source, &target, observer, errors) {
+                Err(error) if error.kind() == io::ErrorKind::Unsupported => {
+                    errors.push(format!("{}: {error}", source.display()));
+                }
+                result => result?,
+            }
+        }
+        // Applied last, so read-only folders can still be filled.
+        return fs::set_permissions(target, metadata.permissions());
+    } else if !metadata.is_file() {
+        copy_special(target, &metadata)?;
+    } else {
+        let result = copy_file(source, target, &metadata, observer);
+        if result.is_err() {
+            let _ = fs::remove_file(target);
+        }
+        result?;
+    }
+    observer.file_copied()
+}
+
+fn copy_file(
+    source: &Path,
+    target: &Path,
+    metadata: &fs::Metadata,
+    observer: &mut dyn TransferObserver,
+) -> io::Result<()> {
+    let mut reader = fs::File::open(source)?;
+    let mut writer = fs::File::create_new(target)?;
+    let mut buffer = vec![0; COPY_CHUNK_SIZE];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        writer.write_all(&buffer[..read])?;
+        observer.copied(read as u64)?;
+    }
+    writer.set_permissions(metadata.permissions())
+}
+
+#[cfg(unix)]
+fn copy_special(target: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    if !metadata.file_type().is_fifo() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Sockets and devices cannot be copied",
+        ));
+    }
+    let path = CString::new(target.as_os_str().as_bytes())?;
+    let mode = metadata.permissions().mode() as libc::mode_t;
+    match unsafe { libc::mkfifo(path.as_ptr(), mode) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(windows)]
+fn copy_special(_: &Path, _: &fs::Metadata) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Special files cannot be copied",
+    ))
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    let link = fs::read_link(source)?;
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(link, target)
+    } else {
+        std::os::windows::fs::symlink_file(link, target)
+    }
+}
+
+pub(super) fn remove_recursive(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(root: &Path) {
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::write(root.join("src/a.txt"), b"a").unwrap();
+        fs::write(root.join("src/nested/b.txt"), b"b").unwrap();
+        fs::create_dir(root.join("dest")).unwrap();
+    }
+
+    #[test]
+    fn copies_folders_recursively() {
+        let dir = tempfile::tempdir().unwrap();
+        tree(dir.path());
+
+        let target = transfer(
+            &dir.path().join("src"),
+            &dir.path().join("dest"),
+            TransferMode::Copy,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(target, dir.path().join("dest/src"));
+        assert_eq!(fs::read(target.join("nested/b.txt")).unwrap(), b"b");
+        assert!(dir.path().join("src/a.txt").exists());
+    }
+
+    #[test]
+    fn moves_files() {
+        let dir = tempfile::tempdir().unwrap();
+        tree(dir.path());
+
+        let target = transfer(
+            &dir.path().join("src/a.txt"),
+            &dir.path().join("dest"),
+            TransferMode::Move,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(target).unwrap(), b"a");
+        assert!(!dir.path().join("src/a.txt").exists());
+    }
+
+    #[test]
+    fn never_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        tree(dir.path());
+        fs::write(dir.path().join("dest/a.txt"), b"kept").unwrap();
+
+        let error = transfer(
+            &dir.path().join("src/a.txt"),
+            &dir.path().join("dest"),
+            TransferMode::Copy,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(dir.path().join("dest/a.txt")).unwrap(), b"kept");
+    }
+
+    #[test]
+    fn refuses_folder_into_itself() {
+        let dir = tempfile::tempdir().unwrap();
+        tree(dir.path());
+
+        let source = dir.path().join("src");
+        let error = transfer(
+            &source,
+            &source.join("nested"),
+            TransferMode::Move,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(source.join("nested/b.txt").exists());
+    }
+
+    #[test]
+    fn pastes_copies_next_to_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        tree(dir.path());
+        let src = dir.path().join("src");
+
+        let first = paste(
+            &src.join("a.txt"),
+            &src,
+            TransferMode::Copy,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let second = paste(
+            &src.join("a.txt"),
+            &src,
+            TransferMode::Copy,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let folder = paste(
+            &src.join("nested"),
+            &src,
+            TransferMode::Copy,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let moved = paste(
+            &src.join("a.txt"),
+            &src,
+            TransferMode::Move,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(first, src.join("a (copy).txt"));
+        assert_eq!(second, src.join("a (copy 2).txt"));
+        assert_eq!(fs::read(folder.join("b.txt")).unwrap(), b"b");
+        assert_eq!(folder, src.join("nested (copy)"));
+        assert_eq!(moved, src.join("a.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copies_symlinks_as_links() {
+        let dir = tempfile::tempdir().unwrap();
+        tree(dir.path());
+        std::os::unix::fs::symlink("a.txt", dir.path().join("src/link")).unwrap();
+
+        transfer(
+            &dir.path().join("src"),
+            &dir.path().join("dest"),
+            TransferMode::Copy,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        let link = dir.path().join("dest/src/link");
+        assert_eq!(fs::read_link(link).unwrap(), Path::new("a.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copies_pipes_as_new_pipes() {
+        use std::os::unix::fs::FileTypeExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        tree(dir.path());
+        let path = std::ffi::CString::new(
+            dir.path()
+                .join("src/pipe")
+                .into_os_string()
+                .into_encoded_bytes(),
+        )
+        .unwrap();
+        assert_eq!(unsafe { libc::mkfifo(path.as_ptr(), 0o644) }, 0);
+
+        let mut errors = Vec::new();
+        transfer(
+            &dir.path().join("src"),
+            &dir.path().join("dest"),
+            TransferMode::Copy,
+            &mut (),
+            &mut errors,
+        )
+        .unwrap();
+
+        let pipe = dir.path().join("dest/src/pipe").symlink_metadata().unwrap();
+        assert!(pipe.file_type().is_fifo());
+        assert!(errors.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_sockets_in_copied_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        tree(dir.path());
+        let _socket =
+            std::os::unix::net::UnixListener::bind(dir.path().join("src/socket")).unwrap();
+
+        let mut errors = Vec::new();
+        transfer(
+            &dir.path().join("src"),
+            &dir.path().join("dest"),
+            TransferMode::Copy,
+            &mut (),
+            &mut errors,
+        )
+        .unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert!(!dir.path().join("dest/src/socket").exists());
+        assert_eq!(fs::read(dir.path().join("dest/src/a.txt")).unwrap(), b"a");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pastes_copies_of_names_that_are_not_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join(OsStr::from_bytes(b"caf\xe9.txt"));
+        fs::write(&source, b"a").unwrap();
+
+        let copy = paste(
+            &source,
+            dir.path(),
+            TransferMode::Copy,
+            &mut (),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(copy.file_name().unwrap().as_bytes(), b"caf\xe9 (copy).txt");
+    }
+}
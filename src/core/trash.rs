@@ -0,0 +1,500 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::data_dir;
+use super::local_time::LocalTime;
+use super::transfer::{move_to, remove_recursive};
+use super::uri::{decode_path, encode_path};
+
+/// The location of the trash browser.
+pub const TRASH_URI: &str = "trash:///";
+
+const INFO_EXTENSION: &str = ".trashinfo";
+
+/// A trash directory with its `files` and `info` folders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrashDir {
+    path: PathBuf,
+    /// The top directory of the volume, which original paths are relative to;
+    /// `None` for the home trash, which keeps absolute paths.
+    top_dir: Option<PathBuf>,
+}
+
+impl TrashDir {
+    fn files(&self) -> PathBuf {
+        self.path.join("files")
+    }
+
+    fn info(&self) -> PathBuf {
+        self.path.join("info")
+    }
+}
+
+/// An item in one of the trash directories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedItem {
+    /// The trashed file, in the `files` folder.
+    pub path: PathBuf,
+    pub original_path: PathBuf,
+    pub deleted: Option<LocalTime>,
+    info: PathBuf,
+}
+
+impl TrashedItem {
+    /// The name the item had before it was trashed.
+    pub fn name(&self) -> String {
+        self.original_path
+            .file_name()
+            .unwrap_or(self.path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// The trash of the user's home volume, `$XDG_DATA_HOME/Trash`.
+pub fn home_trash() -> Option<PathBuf> {
+    data_dir().map(|data| data.join("Trash"))
+}
+
+/// Moves `path` into the trash of its volume and returns where it ended up.
+pub fn trash(path: &Path) -> io::Result<PathBuf> {
+    trash_into(path, &trash_dir_for(path)?, LocalTime::now())
+}
+
+/// Writes the info file first, as the specification asks, which also reserves
+/// a free name: `name`, `name.2`, `name.3`, …
+fn trash_into(path: &Path, trash: &TrashDir, deleted: LocalTime) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Cannot trash a root"))?;
+    let (files, info) = (trash.files(), trash.info());
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+
+    let original = match &trash.top_dir {
+        Some(top_dir) => path.strip_prefix(top_dir).unwrap_or(path),
+        None => path,
+    };
+    let content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(original),
+        deleted.format_iso()
+    );
+
+    for index in 1.. {
+        let mut trashed_name = name.to_os_string();
+        if index > 1 {
+            trashed_name.push(format!(".{index}"));
+        }
+        let target = files.join(&trashed_name);
+        let mut info_name = trashed_name;
+        info_name.push(INFO_EXTENSION);
+        let info_file = info.join(info_name);
+
+        let mut file = match fs::File::create_new(&info_file) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        };
+        // A stray file without info would be overwritten otherwise.
+        if target.symlink_metadata().is_ok() {
+            let _ = fs::remove_file(&info_file);
+            continue;
+        }
+        let result = file
+            .write_all(content.as_bytes())
+            .and_then(|()| fs::rename(path, &target));
+        if result.is_err() {
+            let _ = fs::remove_file(&info_file);
+        }
+        return result.map(|()| target);
+    }
+    unreachable!()
+}
+
+/// The home trash for paths on the home volume, the top directory trash of
+/// their volume otherwise.
+fn trash_dir_for(path: &Path) -> io::Result<TrashDir> {
+    let home = home_trash()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Data directory not found"))?;
+    #[cfg(unix)]
+    {
+        let path_device = device(path)?;
+        let home_device = home.ancestors().find_map(|dir| device(dir).ok());
+        if home_device != Some(path_device) {
+            return top_dir_trash(path, path_device);
+        }
+    }
+    Ok(TrashDir {
+        path: home,
+        top_dir: None,
+    })
+}
+
+#[cfg(unix)]
+fn device(path: &Path) -> io::Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    path.symlink_metadata().map(|metadata| metadata.dev())
+}
+
+/// `$topdir/.Trash/$uid` when the administrator set up a shared `.Trash`
+/// (a sticky folder, not a link), `$topdir/.Trash-$uid` otherwise.
+#[cfg(unix)]
+fn top_dir_trash(path: &Path, device_id: u64) -> io::Result<TrashDir> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let top_dir = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| device(dir).ok() == Some(device_id))
+        .last()
+        .ok_or_else(|| io::Error::other("Cannot find the top directory of the volume"))?
+        .to_path_buf();
+    let uid = super::users::current_uid();
+
+    let shared = top_dir.join(".Trash");
+    let is_shared = shared
+        .symlink_metadata()
+        .is_ok_and(|metadata| metadata.is_dir() && metadata.permissions().mode() & 0o1000 != 0);
+    if is_shared {
+        let path = shared.join(uid.to_string());
+        if fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&path)
+            .is_ok()
+        {
+            return Ok(TrashDir {
+                path,
+                top_dir: Some(top_dir),
+            });
+        }
+    }
+
+    let path = top_dir.join(format!(".Trash-{uid}"));
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&path)?;
+    if path.symlink_metadata()?.is_symlink() {
+        return Err(io::Error::other(format!(
+            "{} is not a trash directory",
+            path.display()
+        )));
+    }
+    Ok(TrashDir {
+        path,
+        top_dir: Some(top_dir),
+    })
+}
+
+/// The home trash and the trash directories found on mounted volumes.
+fn trash_dirs() -> Vec<TrashDir> {
+    let home = home_trash().map(|path| TrashDir {
+        path,
+        top_dir: None,
+    });
+    home.into_iter().chain(volume_trash_dirs()).collect()
+}
+
+#[cfg(unix)]
+fn volume_trash_dirs() -> Vec<TrashDir> {
+    let uid = super::users::current_uid();
+    let mut dirs: Vec<TrashDir> = Vec::new();
+    for top_dir in mount_points() {
+        for path in [
+            top_dir.join(".Trash").join(uid.to_string()),
+            top_dir.join(format!(".Trash-{uid}")),
+        ] {
+            // Bind mounts show the same folder more than once.
+            if path.is_dir() && !dirs.iter().any(|dir| dir.path == path) {
+                dirs.push(TrashDir {
+                    path,
+                    top_dir: Some(top_dir.clone()),
+                });
+            }
+        }
+    }
+    dirs
+}
+
+#[cfg(not(unix))]
+fn volume_trash_dirs() -> Vec<TrashDir> {
+    Vec::new()
+}
+
+#[cfg(unix)]
+fn mount_points() -> Vec<PathBuf> {
+    fs::read_to_string("/proc/self/mounts")
+        .map(|content| parse_mount_points(&content))
+        .unwrap_or_default()
+}
+
+/// The second field of each `/proc/self/mounts` line, where spaces, tabs, newlines
+/// and backslashes are escaped as octal.
+#[cfg(unix)]
+fn parse_mount_points(content: &str) -> Vec<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    content
+        .lines()
+        .filter_map(|line| line.split(' ').nth(1))
+        .map(|field| {
+            let mut bytes = Vec::with_capacity(field.len());
+            let mut rest = field.as_bytes();
+            while let Some((&byte, tail)) = rest.split_first() {
+                let escaped = tail
+                    .get(..3)
+                    .and_then(|digits| std::str::from_utf8(digits).ok())
+                    .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+                match (byte, escaped) {
+                    (b'\\', Some(escaped)) => {
+                        bytes.push(escaped);
+                        rest = &tail[3..];
+                    }
+                    _ => {
+                        bytes.push(byte);
+                        rest = tail;
+                    }
+                }
+            }
+            PathBuf::from(OsString::from_vec(bytes))
+        })
+        .collect()
+}
+
+/// The item trashed from `original_path` to `path`, found without reading the trash.
+pub(super) fn trashed_item(original_path: &Path, path: &Path) -> Option<TrashedItem> {
+    let mut info_name = path.file_name()?.to_os_string();
+    info_name.push(INFO_EXTENSION);
+    Some(TrashedItem {
+        path: path.to_path_buf(),
+        original_path: original_path.to_path_buf(),
+        deleted: None,
+        info: path.parent()?.parent()?.join("info").join(info_name),
+    })
+}
+
+/// Everything in the trash directories, unordered.
+pub fn trashed_items() -> Vec<TrashedItem> {
+    trash_dirs().iter().flat_map(read_trash_dir).collect()
+}
+
+fn read_trash_dir(trash: &TrashDir) -> Vec<TrashedItem> {
+    let Ok(entries) = fs::read_dir(trash.info()) else {
+        return Vec::new();
+    };
+    let files = trash.files();
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let info = entry.path();
+            if info.extension()? != &INFO_EXTENSION[1..] {
+                return None;
+            }
+            let path = files.join(info.file_stem()?);
+            path.symlink_metadata().ok()?;
+
+            let (original_path, deleted) = parse_trash_info(&fs::read_to_string(&info).ok()?)?;
+            let original_path = match &trash.top_dir {
+                Some(top_dir) if original_path.is_relative() => top_dir.join(original_path),
+                _ => original_path,
+            };
+            Some(TrashedItem {
+                path,
+                original_path,
+                deleted,
+                info,
+            })
+        })
+        .collect()
+}
+
+/// The original path and the deletion date of a `.trashinfo` file.
+fn parse_trash_info(content: &str) -> Option<(PathBuf, Option<LocalTime>)> {
+    let mut lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    if lines.next()? != "[Trash Info]" {
+        return None;
+    }
+
+    let (mut path, mut deleted) = (None, None);
+    for line in lines.take_while(|line| !line.starts_with('[')) {
+        match line.split_once('=') {
+            Some(("Path", value)) => path = decode_path(value.trim()),
+            Some(("DeletionDate", value)) => deleted = LocalTime::parse_iso(value),
+            _ => {}
+        }
+    }
+    Some((path?, deleted))
+}
+
+/// Moves the item back where it came from, unless something took its place.
+pub fn restore(item: &TrashedItem) -> io::Result<()> {
+    if item.original_path.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", item.original_path.display()),
+        ));
+    }
+    if let Some(parent) = item.original_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    move_to(&item.path, &item.original_path, &mut ())?;
+    fs::remove_file(&item.info)
+}
+
+pub fn delete_trashed(item: &TrashedItem) -> io::Result<()> {
+    match remove_recursive(&item.path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+        _ => {}
+    }
+    fs::remove_file(&item.info)
+}
+
+/// Deletes everything in all trash directories and returns the errors.
+pub fn empty_trash() -> Vec<String> {
+    let mut errors = Vec::new();
+    for trash in trash_dirs() {
+        for dir in [trash.files(), trash.info()] {
+            for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+                if let Err(error) = remove_recursive(&entry.path()) {
+                    errors.push(format!("{}: {error}", entry.path().display()));
+                }
+            }
+        }
+        let _ = fs::remove_file(trash.path.join("directorysizes"));
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deleted() -> LocalTime {
+        LocalTime::parse_iso("2024-05-04T10:20:30").unwrap()
+    }
+
+    #[test]
+    fn trashes_and_restores_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashDir {
+            path: dir.path().join("Trash"),
+            top_dir: None,
+        };
+        let original = dir.path().join("a b.txt");
+        fs::write(&original, b"a").unwrap();
+
+        let trashed = trash_into(&original, &trash, deleted()).unwrap();
+
+        assert_eq!(trashed, dir.path().join("Trash/files/a b.txt"));
+        assert!(!original.exists());
+        let info = fs::read_to_string(dir.path().join("Trash/info/a b.txt.trashinfo")).unwrap();
+        assert_eq!(
+            info,
+            format!(
+                "[Trash Info]\nPath={}/a%20b.txt\nDeletionDate=2024-05-04T10:20:30\n",
+                encode_path(dir.path())
+            )
+        );
+
+        let items = read_trash_dir(&trash);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].original_path, original);
+        assert_eq!(items[0].deleted, Some(deleted()));
+        assert_eq!(items[0].name(), "a b.txt");
+        let item = trashed_item(&original, &trashed).unwrap();
+        assert_eq!((&item.path, &item.info), (&items[0].path, &items[0].info));
+
+        restore(&item).unwrap();
+        assert_eq!(fs::read(&original).unwrap(), b"a");
+        assert!(read_trash_dir(&trash).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restores_names_that_are_not_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashDir {
+            path: dir.path().join("Trash"),
+            top_dir: None,
+        };
+        let original = dir.path().join(OsStr::from_bytes(b"caf\xe9.txt"));
+        fs::write(&original, b"a").unwrap();
+
+        trash_into(&original, &trash, deleted()).unwrap();
+        let info = fs::read_to_string(
+            trash
+                .info()
+                .join(OsStr::from_bytes(b"caf\xe9.txt.trashinfo")),
+        )
+        .unwrap();
+        assert!(info.contains("/caf%E9.txt\n"));
+
+        let items = read_trash_dir(&trash);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].original_path, original);
+        restore(&items[0]).unwrap();
+        assert_eq!(fs::read(&original).unwrap(), b"a");
+        assert!(read_trash_dir(&trash).is_empty());
+    }
+
+    #[test]
+    fn keeps_items_with_the_same_name_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashDir {
+            path: dir.path().join(".Trash-1000"),
+            top_dir: Some(dir.path().to_path_buf()),
+        };
+        for folder in ["one", "two"] {
+            fs::create_dir_all(dir.path().join(folder)).unwrap();
+            fs::write(dir.path().join(folder).join("notes"), folder).unwrap();
+            trash_into(&dir.path().join(folder).join("notes"), &trash, deleted()).unwrap();
+        }
+
+        let info = fs::read_to_string(trash.info().join("notes.2.trashinfo")).unwrap();
+        assert!(info.contains("\nPath=two/notes\n"));
+
+        let mut items = read_trash_dir(&trash);
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(items[1].path, trash.files().join("notes.2"));
+        assert_eq!(items[1].original_path, dir.path().join("two/notes"));
+
+        delete_trashed(&items[0]).unwrap();
+        assert!(!trash.files().join("notes").exists());
+        assert_eq!(read_trash_dir(&trash).len(), 1);
+    }
+
+    #[test]
+    fn reads_only_valid_trash_info() {
+        assert_eq!(
+            parse_trash_info("[Trash Info]\nPath=/srv/a%20b\nDeletionDate=bad\n"),
+            Some((PathBuf::from("/srv/a b"), None))
+        );
+        assert_eq!(parse_trash_info("[Desktop Entry]\nPath=/srv/a\n"), None);
+        assert_eq!(
+            parse_trash_info("[Trash Info]\nDeletionDate=2024-05-04T10:20:30\n"),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parses_escaped_mount_points() {
+        let content = "/dev/sda1 / ext4 rw 0 0\n/dev/sdb1 /media/me/USB\\040Stick vfat rw 0 0\n";
+
+        assert_eq!(
+            parse_mount_points(content),
+            vec![PathBuf::from("/"), PathBuf::from("/media/me/USB Stick")]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// The `file://` URI of an absolute path.
+pub fn file_uri(path: &Path) -> String {
+    format!("file://{}", encode_path(path))
+}
+
+/// The path with everything but unreserved characters and slashes percent-encoded,
+/// byte by byte so that names that are not UTF-8 survive.
+pub fn encode_path(path: &Path) -> String {
+    let mut encoded = String::new();
+    for &byte in path_bytes(path).iter() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+pub fn decode_path(encoded: &str) -> Option<PathBuf> {
+    percent_decode(encoded).and_then(path_from_bytes)
+}
+
+/// The local path of a `file://` URI, `None` for other schemes.
+pub fn file_uri_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let path = path.strip_prefix("localhost").unwrap_or(path);
+    if !path.starts_with('/') {
+        return None;
+    }
+    decode_path(path)
+}
+
+fn percent_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(windows)]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    Cow::Owned(path.to_string_lossy().into_owned().into_bytes())
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    Some(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+#[cfg(windows)]
+fn path_from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_file_uris() {
+        let path = Path::new("/home/me/a b#ć");
+
+        assert_eq!(file_uri(path), "file:///home/me/a%20b%23%C4%87");
+        assert_eq!(file_uri_path(&file_uri(path)).as_deref(), Some(path));
+        assert_eq!(
+            file_uri_path("file://localhost/srv/data"),
+            Some(PathBuf::from("/srv/data"))
+        );
+        assert_eq!(file_uri_path("file://host/srv/data"), None);
+        assert_eq!(file_uri_path("sftp://host/share"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_names_that_are_not_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/a\xff b"));
+
+        assert_eq!(file_uri(path), "file:///tmp/a%FF%20b");
+        assert_eq!(file_uri_path(&file_uri(path)).as_deref(), Some(path));
+        assert_eq!(decode_path(&encode_path(path)).as_deref(), Some(path));
+    }
+}
@@ -0,0 +1,148 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserDir {
+    Desktop,
+    Documents,
+    Downloads,
+    Music,
+    Pictures,
+    Videos,
+    Templates,
+}
+
+impl UserDir {
+    pub const ALL: [UserDir; 7] = [
+        UserDir::Desktop,
+        UserDir::Documents,
+        UserDir::Downloads,
+        UserDir::Music,
+        UserDir::Pictures,
+        UserDir::Videos,
+        UserDir::Templates,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            UserDir::Desktop => "XDG_DESKTOP_DIR",
+            UserDir::Documents => "XDG_DOCUMENTS_DIR",
+            UserDir::Downloads => "XDG_DOWNLOAD_DIR",
+            UserDir::Music => "XDG_MUSIC_DIR",
+            UserDir::Pictures => "XDG_PICTURES_DIR",
+            UserDir::Videos => "XDG_VIDEOS_DIR",
+            UserDir::Templates => "XDG_TEMPLATES_DIR",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            UserDir::Desktop => "Desktop",
+            UserDir::Documents => "Documents",
+            UserDir::Downloads => "Downloads",
+            UserDir::Music => "Music",
+            UserDir::Pictures => "Pictures",
+            UserDir::Videos => "Videos",
+            UserDir::Templates => "Templates",
+        }
+    }
+}
+
+/// Falls back to `/etc/passwd` when `$HOME` is not set.
+pub fn home_dir() -> Option<PathBuf> {
+    let home = env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from);
+    #[cfg(unix)]
+    let home = home.or_else(|| super::users::user_home(super::users::current_uid()));
+    home
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".local/share")))
+}
+
+pub fn user_dirs() -> Vec<(UserDir, PathBuf)> {
+    let (Some(home), Some(config)) = (home_dir(), config_dir()) else {
+        return Vec::new();
+    };
+    fs::read_to_string(config.join("user-dirs.dirs"))
+        .map(|content| parse_user_dirs(&content, &home))
+        .unwrap_or_default()
+}
+
+/// Folders set to the home directory itself are disabled and left out.
+pub fn parse_user_dirs(content: &str, home: &Path) -> Vec<(UserDir, PathBuf)> {
+    let values: Vec<(&str, PathBuf)> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+            let path = match value.strip_prefix("$HOME") {
+                Some(relative) => home.join(relative.trim_start_matches('/')),
+                None if value.starts_with('/') => PathBuf::from(value),
+                None => return None,
+            };
+            Some((key.trim(), path))
+        })
+        .collect();
+
+    UserDir::ALL
+        .into_iter()
+        .filter_map(|dir| {
+            let (_, path) = values.iter().rev().find(|(key, _)| *key == dir.key())?;
+            (path != home).then(|| (dir, path.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_user_dirs() {
+        let content = r#"
+# This file is written by xdg-user-dirs-update
+XDG_DOWNLOAD_DIR="$HOME/Downloads"
+XDG_DOCUMENTS_DIR="$HOME/My Documents"
+XDG_MUSIC_DIR="/srv/music"
+"#;
+
+        assert_eq!(
+            parse_user_dirs(content, Path::new("/home/me")),
+            vec![
+                (UserDir::Documents, PathBuf::from("/home/me/My Documents")),
+                (UserDir::Downloads, PathBuf::from("/home/me/Downloads")),
+                (UserDir::Music, PathBuf::from("/srv/music")),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_disabled_and_malformed_entries() {
+        let content = r#"
+XDG_DESKTOP_DIR="$HOME/"
+XDG_TEMPLATES_DIR="$HOME"
+XDG_VIDEOS_DIR=$HOME/Videos
+XDG_PICTURES_DIR="relative/Pictures"
+XDG_PUBLICSHARE_DIR="$HOME/Public"
+"#;
+
+        assert!(parse_user_dirs(content, Path::new("/home/me")).is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Resolves a user id to a login name using `/etc/passwd`, read once per process.
+pub fn user_name(uid: u32) -> Option<&'static str> {
+    static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    USERS
+        .get_or_init(|| {
+            fs::read_to_string("/etc/passwd")
+                .map(|content| parse_names(&content))
+                .unwrap_or_default()
+        })
+        .get(&uid)
+        .map(String::as_str)
+}
+
+/// The home directory `/etc/passwd` gives for a user id.
+pub fn user_home(uid: u32) -> Option<PathBuf> {
+    let content = fs::read_to_string("/etc/passwd").ok()?;
+    parse_home(&content, uid)
+}
+
+/// The id of the user running the application.
+#[cfg(unix)]
+pub fn current_uid() -> u32 {
+    // SAFETY: `getuid` cannot fail.
+    unsafe { libc::getuid() }
+}
+
+/// Parses `name:password:id:...` records shared by `/etc/passwd` and `/etc/group`.
+fn parse_names(content: &str) -> HashMap<u32, String> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_owned()))
+        })
+        .collect()
+}
+
+/// Finds the home field of the `name:password:uid:gid:gecos:home:shell` record of `uid`.
+fn parse_home(content: &str, uid: u32) -> Option<PathBuf> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            match fields[..] {
+                [_, _, id, _, _, home, ..] if id.parse() == Ok(uid) && !home.is_empty() => {
+                    Some(PathBuf::from(home))
+                }
+                _ => None,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_passwd_records() {
+        let names = parse_names(
+            "# comment\nroot:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/sh\nbroken\n",
+        );
+
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.get(&0).map(String::as_str), Some("root"));
+        assert_eq!(names.get(&1000).map(String::as_str), Some("alice"));
+    }
+
+    #[test]
+    fn finds_home_of_user() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\nnobody:x:65534:65534:::/bin/false\n";
+
+        assert_eq!(parse_home(passwd, 0), Some(PathBuf::from("/root")));
+        assert_eq!(parse_home(passwd, 65534), None);
+        assert_eq!(parse_home(passwd, 1000), None);
+    }
+}
@@ -0,0 +1,65 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::core::CommandLine;
+use crate::ui::items_box::ViewMode;
+
+pub struct AppInit {
+    _current_dir: Option<String>,
+    _selection: Option<PathBuf>,
+    _show_hidden_items: bool,
+    _view_mode: ViewMode,
+    _new_window: bool,
+}
+
+impl AppInit {
+    /// A window at `current_dir`, or at the home directory.
+    pub fn new(current_dir: Option<String>) -> Self {
+        Self {
+            _current_dir: current_dir,
+            _selection: None,
+            _show_hidden_items: false,
+            _view_mode: ViewMode::List,
+            _new_window: false,
+        }
+    }
+
+    /// Reads the arguments following the program name, with relative paths taken from `cwd`.
+    pub fn from_args(args: impl IntoIterator<Item = OsString>, cwd: &Path) -> Result<Self, String> {
+        let command_line = CommandLine::parse(args)?;
+        let view_mode = match command_line.view.as_deref() {
+            Some(id) => ViewMode::from_id(id)
+                .ok_or_else(|| format!("Unknown view {id}, expected list, grid or details"))?,
+            None => ViewMode::List,
+        };
+        let (current_dir, selection) = command_line.location(cwd)?.unzip();
+
+        Ok(Self {
+            _current_dir: current_dir.map(|dir| dir.display().to_string()),
+            _selection: selection.flatten(),
+            _show_hidden_items: command_line.show_hidden,
+            _view_mode: view_mode,
+            _new_window: command_line.new_window,
+        })
+    }
+
+    pub fn current_dir(&self) -> Option<&str> {
+        self._current_dir.as_deref()
+    }
+
+    pub fn selection(&self) -> Option<&Path> {
+        self._selection.as_deref()
+    }
+
+    pub fn show_hidden_items(&self) -> bool {
+        self._show_hidden_items
+    }
+
+    pub(crate) fn view_mode(&self) -> ViewMode {
+        self._view_mode
+    }
+
+    pub fn new_window(&self) -> bool {
+        self._new_window
+    }
+}
@@ -1,28 +1,95 @@
 mod core;
+mod init;
 mod menubar;
 mod shortcuts;
 mod ui;
 
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use crate::core::{
+    JobEvent, JobId, JobQueue, Journal, NewEntry, Operation, TRASH_URI, TransferMode,
+};
+pub use init::AppInit;
 use menubar::setup_menubar;
 use relm4::{Component, ComponentController, ComponentParts, Controller};
 
-use relm4::gtk::prelude::*;
+use relm4::gtk::{glib, prelude::*};
 use relm4::prelude::*;
-use shortcuts::setup_shortcuts;
+use shortcuts::{
+    PANE_ACTIONS, set_action_enabled, set_action_state, setup_accels, setup_shortcuts,
+    sort_option_action,
+};
 
-use std::path::Path;
 use ui::{
-    items_box::{ItemsBox, ItemsBoxInput, ItemsBoxOutput},
+    dialogs::report_errors,
+    history::{History, HistoryInput, HistoryOutput},
+    items_box::{Column, ItemsBoxInput, SortKey, SortOption, Sorting, ViewMode, Zoom},
+    panes::{Panes, PanesInput, PanesOutput},
+    sidebar::{Sidebar, SidebarInput, SidebarOutput},
+    tabs::{TabsInput, TabsOutput},
     top_panel::{TopPanel, TopPanelInput, TopPanelOutput},
+    trash::{Trash, TrashInput},
 };
 
+const FILES_PAGE: &str = "files";
+const TRASH_PAGE: &str = "trash";
+
+thread_local! {
+    /// The windows opened after the first one, dropped once closed so that their
+    /// jobs, watchers and monitors stop with them.
+    static WINDOWS: RefCell<Vec<Controller<App>>> = RefCell::default();
+}
+
 #[derive(Debug)]
 pub enum AppInput {
     UpdateCurrentDirectory(String),
-    Back,
+    HistoryChanged(Vec<String>, Vec<String>),
+    Navigate(String),
+    EditLocation,
+    Back(usize),
+    Forward(usize),
+    Up,
     Home,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    NewWindow,
+    CloseWindow,
+    ToggleSplit,
+    SplitChanged(bool),
+    ShowSidebar(bool),
+    BookmarkCurrentDirectory,
+    SwitchPane,
+    SyncPanes,
+    TransferToOtherPane(TransferMode),
+    CopyToClipboard(TransferMode),
+    Paste,
+    TrashSelection,
+    DeleteSelection,
+    StartRename,
+    Create(NewEntry),
+    StartJob(Operation),
+    Job(JobEvent),
+    PauseJob(JobId, bool),
+    CancelJob(JobId),
+    /// Lists the errors of a finished job.
+    JobFailed(String, Vec<String>),
+    Undo,
+    Redo,
+    ShowHistory,
     ToggleShowHiddenItems,
     ShowHiddenItems(bool),
+    HiddenItemsChanged(bool),
+    SetViewMode(ViewMode),
+    ViewModeChanged(ViewMode),
+    ZoomIn,
+    ZoomOut,
+    SetColumnVisible(Column, bool),
+    SortBy(SortKey),
+    SetSortOption(SortOption, bool),
+    SortingChanged(Sorting),
     Error(String),
 }
 
@@ -30,22 +97,98 @@ pub enum AppInput {
 pub struct App {
     #[tracker::do_not_track]
     home_dir: String,
-    #[tracker::do_not_track]
-    parent_dir: Option<String>,
     current_dir: String,
     #[tracker::do_not_track]
     top_panel: Controller<TopPanel>,
     #[tracker::do_not_track]
-    items_box: Controller<ItemsBox>,
+    sidebar: Controller<Sidebar>,
+    #[tracker::do_not_track]
+    panes: Controller<Panes>,
+    #[tracker::do_not_track]
+    trash: Controller<Trash>,
+    /// The files of the panes or the trash, whichever is shown.
+    #[tracker::do_not_track]
+    pages: gtk::Stack,
+    /// The zoom level shared by all panes and tabs of the window.
+    #[tracker::do_not_track]
+    zoom: Zoom,
+    #[tracker::do_not_track]
+    jobs: JobQueue,
+    #[tracker::do_not_track]
+    journal: Journal,
+    #[tracker::do_not_track]
+    history: Controller<History>,
 }
 
-fn parent(path: &str) -> Option<String> {
-    Path::new(path).parent().map(|v| v.display().to_string())
+impl App {
+    /// Installs the menubar and the shortcuts shared by all windows, once on startup.
+    pub fn setup_application(app: &gtk::Application) {
+        setup_accels(app);
+        setup_menubar(app);
+    }
+
+    /// Opens another window of the application.
+    pub fn open_window(init: AppInit) {
+        let controller = App::builder().launch(init).detach();
+        let window = controller.widget().clone();
+        relm4::main_application().add_window(&window);
+        window.connect_close_request(|window| {
+            let window = window.clone();
+            // Dropped once the window is gone, outside of its own signal handler.
+            glib::idle_add_local_once(move || {
+                let closed = WINDOWS.with_borrow_mut(|windows| {
+                    let position = windows.iter().position(|c| *c.widget() == window)?;
+                    Some(windows.remove(position))
+                });
+                drop(closed);
+            });
+            glib::Propagation::Proceed
+        });
+        window.present();
+        WINDOWS.with_borrow_mut(|windows| windows.push(controller));
+    }
+
+    /// Sends a message to the items box of the selected tab of the active pane.
+    fn emit_current(&self, message: ItemsBoxInput) {
+        self.emit_tabs(TabsInput::Current(message));
+    }
+
+    /// Sends a message to the tabs of the active pane.
+    fn emit_tabs(&self, message: TabsInput) {
+        self.panes.emit(PanesInput::Current(message));
+    }
+
+    fn showing_trash(&self) -> bool {
+        self.pages.visible_child_name().as_deref() == Some(TRASH_PAGE)
+    }
+
+    fn show_trash(&self) {
+        self.pages.set_visible_child_name(TRASH_PAGE);
+        self.trash.emit(TrashInput::Refresh);
+        self.sidebar
+            .emit(SidebarInput::SetDirectory(TRASH_URI.to_owned()));
+    }
+
+    /// Enables undo and redo as the journal allows and shows its entries.
+    fn journal_changed(&self, root: &gtk::ApplicationWindow) {
+        let (can_undo, can_redo) = (self.journal.can_undo(), self.journal.can_redo());
+        set_action_enabled(root, "undo", can_undo);
+        set_action_enabled(root, "redo", can_redo);
+        let entries = self.journal.entries().cloned().collect();
+        self.history
+            .emit(HistoryInput::Update(entries, can_undo, can_redo));
+    }
+
+    fn show_files(&self) {
+        self.pages.set_visible_child_name(FILES_PAGE);
+        self.sidebar
+            .emit(SidebarInput::SetDirectory(self.current_dir.clone()));
+    }
 }
 
 #[relm4::component(pub)]
 impl Component for App {
-    type Init = ();
+    type Init = AppInit;
     type Input = AppInput;
     type Output = ();
     type CommandOutput = ();
@@ -57,89 +200,273 @@ impl Component for App {
             set_width_request: 1240,
             set_height_request: 680,
             set_show_menubar: true,
+            add_controller = gtk::GestureClick {
+                set_button: 0,
+                set_propagation_phase: gtk::PropagationPhase::Capture,
+                connect_pressed[sender] => move |gesture, _, _, _| {
+                    match gesture.current_button() {
+                        8 => sender.input(AppInput::Back(1)),
+                        9 => sender.input(AppInput::Forward(1)),
+                        _ => {}
+                    }
+                },
+            },
 
             gtk::Box {
                 set_orientation: gtk::Orientation::Vertical,
                 set_spacing: 5,
 
                 model.top_panel.widget(),
-                model.items_box.widget(),
+                gtk::Paned {
+                    set_vexpand: true,
+                    set_position: 200,
+                    set_resize_start_child: false,
+                    set_shrink_start_child: false,
+                    set_start_child: Some(model.sidebar.widget()),
+                    set_end_child: Some(&model.pages),
+                },
             }
         }
     }
 
     fn init(
-        _: Self::Init,
+        init: Self::Init,
         root: Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let home_dir = option_env!("HOME")
-            .or_else(|| option_env!("USERPROFILE"))
-            .expect("Home directory not found");
-
-        let parent_dir = parent(home_dir);
+        let home_dir = crate::core::home_dir()
+            .unwrap_or_else(|| PathBuf::from("/"))
+            .display()
+            .to_string();
+        let current_dir = init.current_dir().unwrap_or(&home_dir).to_owned();
 
         let model = Self {
-            home_dir: home_dir.to_owned(),
-            items_box: ItemsBox::builder()
-                .launch(ItemsBox::init(home_dir, false))
-                .forward(sender.input_sender(), convert_items_box_response),
+            panes: Panes::builder()
+                .launch(Panes::init(&current_dir, init.show_hidden_items()))
+                .forward(sender.input_sender(), convert_panes_response),
+            sidebar: Sidebar::builder()
+                .launch(Sidebar::init(&current_dir))
+                .forward(sender.input_sender(), convert_sidebar_response),
             top_panel: TopPanel::builder()
-                .launch(TopPanel::init(parent_dir.is_some(), false))
+                .launch(TopPanel::init(&current_dir, init.show_hidden_items()))
                 .forward(sender.input_sender(), convert_top_panel_response),
-            parent_dir,
-            current_dir: home_dir.to_owned(),
+            trash: Trash::builder().launch(()).detach(),
+            pages: gtk::Stack::new(),
+            zoom: Zoom::default(),
+            jobs: JobQueue::new({
+                let input_sender = sender.input_sender().clone();
+                move |event| input_sender.emit(AppInput::Job(event))
+            }),
+            journal: Journal::default(),
+            history: History::builder()
+                .launch(())
+                .forward(sender.input_sender(), convert_history_response),
+            home_dir,
+            current_dir,
             tracker: 0,
         };
+        model
+            .pages
+            .add_named(model.panes.widget(), Some(FILES_PAGE));
+        model
+            .pages
+            .add_named(model.trash.widget(), Some(TRASH_PAGE));
+        model.emit_current(ItemsBoxInput::SetViewMode(init.view_mode()));
+        if let Some(selection) = init.selection() {
+            model.emit_current(ItemsBoxInput::Select(selection.to_path_buf()));
+        }
 
-        setup_shortcuts(&relm4::main_application(), &sender);
-        setup_menubar(&relm4::main_application());
+        setup_shortcuts(&root, &sender);
+        set_action_state(&root, "view_mode", init.view_mode().id().to_variant());
+        model.journal_changed(&root);
         let widgets = view_output!();
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, message: Self::Input, _: ComponentSender<Self>, _: &Self::Root) {
+    fn update(&mut self, message: Self::Input, _: ComponentSender<Self>, root: &Self::Root) {
         self.reset();
         match message {
             AppInput::UpdateCurrentDirectory(current_dir) => {
+                self.top_panel
+                    .emit(TopPanelInput::DirectoryLoaded(current_dir.clone()));
+                self.sidebar
+                    .emit(SidebarInput::SetDirectory(current_dir.clone()));
                 self.set_current_dir(current_dir);
-                self.parent_dir = parent(self.get_current_dir());
+                self.pages.set_visible_child_name(FILES_PAGE);
+            }
+            AppInput::HistoryChanged(back, forward) => {
                 self.top_panel
-                    .emit(TopPanelInput::DirectoryLoaded(self.parent_dir.is_some()));
+                    .emit(TopPanelInput::HistoryChanged(back, forward));
+            }
+            AppInput::Navigate(path) if path == TRASH_URI => self.show_trash(),
+            AppInput::Navigate(path) => self.emit_current(ItemsBoxInput::LoadDirectory(path)),
+            AppInput::EditLocation => self.top_panel.emit(TopPanelInput::EditLocation),
+            // Going back from the trash returns to the folder it was opened from.
+            AppInput::Back(_) if self.showing_trash() => self.show_files(),
+            AppInput::Back(steps) => self.emit_current(ItemsBoxInput::Back(steps)),
+            AppInput::Forward(steps) => self.emit_current(ItemsBoxInput::Forward(steps)),
+            AppInput::Up => self.emit_current(ItemsBoxInput::Up),
+            AppInput::Home => {
+                self.emit_current(ItemsBoxInput::LoadDirectory(self.home_dir.clone()));
             }
-            AppInput::Back => {
-                if let Some(parent) = self.parent_dir.clone() {
-                    self.items_box.emit(ItemsBoxInput::LoadDirectory(parent));
+            AppInput::NewTab => self.emit_tabs(TabsInput::NewTab),
+            AppInput::CloseTab => self.emit_tabs(TabsInput::CloseTab),
+            AppInput::NextTab => self.emit_tabs(TabsInput::NextTab),
+            AppInput::PreviousTab => self.emit_tabs(TabsInput::PreviousTab),
+            AppInput::NewWindow => {
+                App::open_window(AppInit::new(Some(self.current_dir.clone())));
+            }
+            AppInput::CloseWindow => root.close(),
+            AppInput::ToggleSplit => self.panes.emit(PanesInput::ToggleSplit),
+            AppInput::SplitChanged(split) => {
+                set_action_state(root, "split", split.to_variant());
+                for action in PANE_ACTIONS {
+                    set_action_enabled(root, action, split);
                 }
             }
-            AppInput::Home => self
-                .items_box
-                .emit(ItemsBoxInput::LoadDirectory(self.home_dir.clone())),
+            AppInput::ShowSidebar(show) => self.sidebar.widget().set_visible(show),
+            AppInput::BookmarkCurrentDirectory => {
+                let path = PathBuf::from(&self.current_dir);
+                self.sidebar.emit(SidebarInput::AddBookmark(path));
+            }
+            AppInput::SwitchPane => self.panes.emit(PanesInput::SwitchPane),
+            AppInput::SyncPanes => self.panes.emit(PanesInput::SyncOtherPane),
+            AppInput::TransferToOtherPane(mode) => {
+                self.panes.emit(PanesInput::TransferToOtherPane(mode));
+            }
+            AppInput::CopyToClipboard(mode) => {
+                self.emit_current(ItemsBoxInput::CopyToClipboard(mode));
+            }
+            AppInput::Paste => self.emit_current(ItemsBoxInput::Paste),
+            AppInput::TrashSelection => self.emit_current(ItemsBoxInput::TrashSelection),
+            AppInput::DeleteSelection => self.emit_current(ItemsBoxInput::DeleteSelection),
+            AppInput::StartRename => self.emit_current(ItemsBoxInput::StartRename),
+            AppInput::Create(entry) => self.emit_current(ItemsBoxInput::Create(entry)),
+            AppInput::StartJob(operation) => {
+                let job = self.jobs.start(operation.clone());
+                self.journal.started(job, operation);
+                self.journal_changed(root);
+            }
+            AppInput::Job(JobEvent::Finished(job, mut report)) => {
+                let changes = std::mem::take(&mut report.changes);
+                let errors = report.errors.len();
+                self.journal
+                    .finished(job, changes, errors, report.cancelled);
+                self.journal_changed(root);
+                self.top_panel
+                    .emit(TopPanelInput::Job(JobEvent::Finished(job, report)));
+            }
+            AppInput::Job(event) => self.top_panel.emit(TopPanelInput::Job(event)),
+            AppInput::PauseJob(id, paused) => self.jobs.pause(id, paused),
+            AppInput::CancelJob(id) => self.jobs.cancel(id),
+            AppInput::JobFailed(description, errors) => {
+                let message = match errors.len() {
+                    1 => format!("{description} finished with an error"),
+                    count => format!("{description} finished with {count} errors"),
+                };
+                report_errors(root, &message, &errors);
+            }
+            AppInput::Undo => {
+                self.journal.undo(|operation| self.jobs.start(operation));
+                self.journal_changed(root);
+            }
+            AppInput::Redo => {
+                self.journal.redo(|operation| self.jobs.start(operation));
+                self.journal_changed(root);
+            }
+            AppInput::ShowHistory => {
+                let window = self.history.widget();
+                window.set_transient_for(Some(root));
+                window.present();
+            }
             AppInput::ToggleShowHiddenItems => {
-                self.items_box.emit(ItemsBoxInput::ToggleShowHiddenItems);
-                self.top_panel.emit(TopPanelInput::ToggleShowHiddenItems);
+                self.emit_current(ItemsBoxInput::ToggleShowHiddenItems);
             }
             AppInput::ShowHiddenItems(show) => {
-                self.items_box.emit(ItemsBoxInput::ShowHiddenItems(show));
+                self.emit_current(ItemsBoxInput::ShowHiddenItems(show));
+            }
+            AppInput::HiddenItemsChanged(show) => {
+                self.top_panel.emit(TopPanelInput::SetShowHiddenItems(show));
+            }
+            AppInput::SetViewMode(view_mode) => {
+                self.emit_current(ItemsBoxInput::SetViewMode(view_mode));
+            }
+            AppInput::ViewModeChanged(view_mode) => {
+                set_action_state(root, "view_mode", view_mode.id().to_variant());
+            }
+            AppInput::ZoomIn => {
+                self.zoom = self.zoom.zoom_in();
+                self.panes.emit(PanesInput::SetZoom(self.zoom));
+            }
+            AppInput::ZoomOut => {
+                self.zoom = self.zoom.zoom_out();
+                self.panes.emit(PanesInput::SetZoom(self.zoom));
+            }
+            AppInput::SetColumnVisible(column, visible) => {
+                self.emit_tabs(TabsInput::SetColumnVisible(column, visible));
             }
-            AppInput::Error(error) => println!("Error occured: [{:?}]", error),
+            AppInput::SortBy(key) => self.emit_current(ItemsBoxInput::SortBy(key)),
+            AppInput::SetSortOption(option, value) => {
+                self.emit_current(ItemsBoxInput::SetSortOption(option, value));
+            }
+            AppInput::SortingChanged(sorting) => {
+                set_action_state(root, "sort_by", sorting.key.id().to_variant());
+                for option in SortOption::ALL {
+                    let state = sorting.option(option).to_variant();
+                    set_action_state(root, &sort_option_action(option), state);
+                }
+            }
+            AppInput::Error(error) => report_errors(root, "An error occurred", &[error]),
         }
     }
 }
 
+fn convert_history_response(output: HistoryOutput) -> AppInput {
+    match output {
+        HistoryOutput::Undo => AppInput::Undo,
+        HistoryOutput::Redo => AppInput::Redo,
+    }
+}
+
 fn convert_top_panel_response(output: TopPanelOutput) -> AppInput {
     match output {
         TopPanelOutput::HiddenItemsToggled(show) => AppInput::ShowHiddenItems(show),
+        TopPanelOutput::Navigate(path) => AppInput::Navigate(path),
         TopPanelOutput::Home => AppInput::Home,
-        TopPanelOutput::Back => AppInput::Back,
+        TopPanelOutput::Back => AppInput::Back(1),
+        TopPanelOutput::Forward => AppInput::Forward(1),
+        TopPanelOutput::Up => AppInput::Up,
+        TopPanelOutput::PauseJob(id, paused) => AppInput::PauseJob(id, paused),
+        TopPanelOutput::CancelJob(id) => AppInput::CancelJob(id),
+        TopPanelOutput::JobFailed(description, errors) => AppInput::JobFailed(description, errors),
     }
 }
 
-fn convert_items_box_response(output: ItemsBoxOutput) -> AppInput {
+fn convert_sidebar_response(output: SidebarOutput) -> AppInput {
     match output {
-        ItemsBoxOutput::DirectoryLoaded(current_dir) => {
-            AppInput::UpdateCurrentDirectory(current_dir)
-        }
-        ItemsBoxOutput::Error(error) => AppInput::Error(error),
+        SidebarOutput::Navigate(path) => AppInput::Navigate(path),
+        SidebarOutput::Error(error) => AppInput::Error(error),
+    }
+}
+
+fn convert_panes_response(output: PanesOutput) -> AppInput {
+    match output {
+        PanesOutput::Tab(output) => convert_tabs_response(output),
+        PanesOutput::SplitChanged(split) => AppInput::SplitChanged(split),
+    }
+}
+
+fn convert_tabs_response(output: TabsOutput) -> AppInput {
+    match output {
+        TabsOutput::DirectoryLoaded(current_dir) => AppInput::UpdateCurrentDirectory(current_dir),
+        TabsOutput::HistoryChanged(back, forward) => AppInput::HistoryChanged(back, forward),
+        TabsOutput::HiddenItemsChanged(show) => AppInput::HiddenItemsChanged(show),
+        TabsOutput::ViewModeChanged(view_mode) => AppInput::ViewModeChanged(view_mode),
+        TabsOutput::SortingChanged(sorting) => AppInput::SortingChanged(sorting),
+        TabsOutput::Error(error) => AppInput::Error(error),
+        TabsOutput::StartJob(operation) => AppInput::StartJob(operation),
+        TabsOutput::ZoomIn => AppInput::ZoomIn,
+        TabsOutput::ZoomOut => AppInput::ZoomOut,
+        TabsOutput::Closed => AppInput::CloseWindow,
     }
 }
@@ -1,7 +1,7 @@
 use anyhow::Result;
-use file_explorer::App;
+use file_explorer::{App, AppInit};
 use relm4::RelmApp;
-use relm4::gtk::{self, gdk, gio};
+use relm4::gtk::{self, gdk, gio, glib, prelude::*};
 
 fn initialize_custom_icons() -> Result<()> {
     gio::resources_register_include!("icons.gresource")?;
@@ -12,10 +12,43 @@ fn initialize_custom_icons() -> Result<()> {
     Ok(())
 }
 
+/// The first launch shows the window created on startup, while later launches
+/// open a new window of the running instance when given a location or
+/// `--new-window`, and raise the active one otherwise.
+fn handle_command_line(
+    app: &gtk::Application,
+    command_line: &gio::ApplicationCommandLine,
+) -> glib::ExitCode {
+    if !command_line.is_remote() {
+        app.activate();
+        return glib::ExitCode::SUCCESS;
+    }
+
+    let cwd = command_line.cwd().unwrap_or_default();
+    let init = match AppInit::from_args(command_line.arguments().into_iter().skip(1), &cwd) {
+        Ok(init) => init,
+        Err(error) => {
+            command_line.printerr_literal(&format!("{error}\n"));
+            return glib::ExitCode::FAILURE;
+        }
+    };
+    match app.active_window() {
+        Some(window) if init.current_dir().is_none() && !init.new_window() => window.present(),
+        _ => App::open_window(init),
+    }
+    glib::ExitCode::SUCCESS
+}
+
 fn main() -> Result<()> {
     let relm_app = RelmApp::new("com.msiarko.file_explorer");
+    let app = relm4::main_application();
+    app.set_flags(app.flags() | gio::ApplicationFlags::HANDLES_COMMAND_LINE);
+    app.connect_command_line(handle_command_line);
+    app.connect_startup(App::setup_application);
     relm4::set_global_css(include_str!("../styles/index.css"));
     initialize_custom_icons()?;
-    relm_app.run::<App>(());
+    let cwd = std::env::current_dir()?;
+    let init = AppInit::from_args(std::env::args_os().skip(1), &cwd).map_err(anyhow::Error::msg)?;
+    relm_app.run::<App>(init);
     Ok(())
 }
@@ -1,31 +1,136 @@
+use std::path::PathBuf;
+
 use relm4::gtk::{Application, gio, prelude::*};
 
+use crate::core::{templates, user_dirs};
+use crate::shortcuts::{
+    ITEMS_COPY, ITEMS_CUT, ITEMS_DELETE, ITEMS_PASTE, ITEMS_REDO, ITEMS_TRASH, ITEMS_UNDO,
+    column_action, history_action, sort_option_action,
+};
+use crate::ui::items_box::{Column, SortKey, SortOption, ViewMode};
+
 pub fn setup_menubar(app: &Application) {
     let menubar = {
         let file_menu = {
             let quit_menu_item = gio::MenuItem::new(Some("Quit"), Some("app.quit"));
 
+            let tabs = gio::Menu::new();
+            tabs.append(Some("New Window"), Some("win.new_window"));
+            tabs.append(Some("New Tab"), Some("win.new_tab"));
+            tabs.append(Some("Close Tab"), Some("win.close_tab"));
+
             let file_menu = gio::Menu::new();
+            file_menu.append_section(None, &new_entry_menu(&templates()));
+            file_menu.append_section(None, &tabs);
             file_menu.append_item(&quit_menu_item);
             file_menu
         };
 
         let view_menu = {
             let show_hidden_items =
-                gio::MenuItem::new(Some("Show Hidden Items"), Some("app.show_hidden"));
+                gio::MenuItem::new(Some("Show Hidden Items"), Some("win.show_hidden"));
+
+            let view_modes = gio::Menu::new();
+            for view_mode in ViewMode::ALL {
+                let action = format!("win.view_mode::{}", view_mode.id());
+                view_modes.append(Some(view_mode.title()), Some(action.as_str()));
+            }
+
+            let zoom = gio::Menu::new();
+            zoom.append(Some("Zoom In"), Some("win.zoom_in"));
+            zoom.append(Some("Zoom Out"), Some("win.zoom_out"));
+
+            let columns = gio::Menu::new();
+            for column in Column::ALL.into_iter().filter(|c| *c != Column::Name) {
+                let action = format!("win.{}", column_action(column));
+                columns.append(Some(column.title()), Some(action.as_str()));
+            }
+
+            let sort_by = {
+                let keys = gio::Menu::new();
+                for key in SortKey::ALL {
+                    let action = format!("win.sort_by::{}", key.id());
+                    keys.append(Some(key.title()), Some(action.as_str()));
+                }
+
+                let options = gio::Menu::new();
+                for option in SortOption::ALL {
+                    let action = format!("win.{}", sort_option_action(option));
+                    options.append(Some(option.title()), Some(action.as_str()));
+                }
+
+                let sort_by = gio::Menu::new();
+                sort_by.append_section(None, &keys);
+                sort_by.append_section(None, &options);
+                sort_by
+            };
 
             let view_menu = gio::Menu::new();
             view_menu.append_item(&show_hidden_items);
+            view_menu.append_section(None, &view_modes);
+            view_menu.append_section(None, &zoom);
+            view_menu.append_submenu(Some("Columns"), &columns);
+            view_menu.append_submenu(Some("Sort By"), &sort_by);
+
+            let panes = gio::Menu::new();
+            panes.append(Some("Show Sidebar"), Some("win.show_sidebar"));
+            panes.append(Some("Split View"), Some("win.split"));
+            panes.append(Some("Switch Pane"), Some("win.switch_pane"));
+            panes.append(
+                Some("Show This Folder in Other Pane"),
+                Some("win.sync_panes"),
+            );
+            view_menu.append_section(None, &panes);
+
+            let tabs = gio::Menu::new();
+            tabs.append(Some("Next Tab"), Some("win.next_tab"));
+            tabs.append(Some("Previous Tab"), Some("win.previous_tab"));
+            view_menu.append_section(None, &tabs);
             view_menu
         };
 
         let edit_menu = {
-            let back = gio::MenuItem::new(Some("Back"), Some("app.back"));
-            let home = gio::MenuItem::new(Some("Home"), Some("app.home"));
+            let back = gio::MenuItem::new(Some("Back"), Some(&history_action("back", 1)));
+            let forward = gio::MenuItem::new(Some("Forward"), Some(&history_action("forward", 1)));
+            let up = gio::MenuItem::new(Some("Up"), Some("win.up"));
+            let home = gio::MenuItem::new(Some("Home"), Some("win.home"));
+            let edit_location =
+                gio::MenuItem::new(Some("Enter Location…"), Some("win.edit_location"));
+
+            let journal = gio::Menu::new();
+            for (label, action, accel) in [
+                ("Undo", "win.undo", ITEMS_UNDO),
+                ("Redo", "win.redo", ITEMS_REDO),
+            ] {
+                let item = gio::MenuItem::new(Some(label), Some(action));
+                item.set_attribute_value("accel", Some(&accel.to_variant()));
+                journal.append_item(&item);
+            }
+            journal.append(Some("History"), Some("win.history"));
+
+            let places = gio::Menu::new();
+            places.append(Some("Bookmark This Folder"), Some("win.bookmark"));
+            for (dir, path) in user_dirs() {
+                let item = gio::MenuItem::new(Some(dir.title()), None);
+                let path = path.display().to_string();
+                item.set_action_and_target_value(Some("win.go_to"), Some(&path.to_variant()));
+                places.append_item(&item);
+            }
+
+            let other_pane = gio::Menu::new();
+            other_pane.append(Some("Copy to Other Pane"), Some("win.copy_to_other_pane"));
+            other_pane.append(Some("Move to Other Pane"), Some("win.move_to_other_pane"));
 
             let edit_menu = gio::Menu::new();
             edit_menu.append_item(&back);
+            edit_menu.append_item(&forward);
+            edit_menu.append_item(&up);
             edit_menu.append_item(&home);
+            edit_menu.append_item(&edit_location);
+            edit_menu.append_section(None, &journal);
+            edit_menu.append_section(None, &selection_menu());
+            edit_menu.append_section(None, &places);
+            edit_menu.append_section(None, &other_pane);
             edit_menu
         };
 
@@ -39,3 +144,42 @@ pub fn setup_menubar(app: &Application) {
 
     app.set_menubar(Some(&menubar));
 }
+
+/// New Folder, New Empty File and a New Document submenu with the given templates.
+pub fn new_entry_menu(templates: &[PathBuf]) -> gio::Menu {
+    let menu = gio::Menu::new();
+    menu.append(Some("New Folder"), Some("win.new_folder"));
+    menu.append(Some("New Empty File"), Some("win.new_file"));
+    if !templates.is_empty() {
+        let documents = gio::Menu::new();
+        for template in templates {
+            let title = template.file_stem().unwrap_or_default().to_string_lossy();
+            let item = gio::MenuItem::new(Some(&title), None);
+            let path = template.display().to_string();
+            item.set_action_and_target_value(Some("win.new_document"), Some(&path.to_variant()));
+            documents.append_item(&item);
+        }
+        menu.append_submenu(Some("New Document"), &documents);
+    }
+    menu
+}
+
+/// The actions on the selected items.
+pub fn selection_menu() -> gio::Menu {
+    // The clipboard and Delete shortcuts belong to the items box, so the menu is told
+    // about them.
+    let menu = gio::Menu::new();
+    for (label, action, accel) in [
+        ("Cut", "win.cut", ITEMS_CUT),
+        ("Copy", "win.copy", ITEMS_COPY),
+        ("Paste", "win.paste", ITEMS_PASTE),
+        ("Move to Trash", "win.trash", ITEMS_TRASH),
+        ("Delete Permanently", "win.delete", ITEMS_DELETE),
+    ] {
+        let item = gio::MenuItem::new(Some(label), Some(action));
+        item.set_attribute_value("accel", Some(&accel.to_variant()));
+        menu.append_item(&item);
+    }
+    menu.append(Some("Rename…"), Some("win.rename"));
+    menu
+}
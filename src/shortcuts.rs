@@ -1,6 +1,13 @@
 use relm4::ComponentSender;
-use relm4::gtk::{Application, gio::ActionEntry, prelude::*};
+use relm4::gtk::{
+    self, Application, ApplicationWindow,
+    gio::{ActionEntry, SimpleAction},
+    glib::{self, Variant, VariantTy},
+    prelude::*,
+};
 
+use crate::core::{NewEntry, TransferMode};
+use crate::ui::items_box::{Column, SortKey, SortOption, Sorting, ViewMode};
 use crate::{App, AppInput};
 
 #[cfg(unix)]
@@ -13,18 +20,93 @@ static TOGGLE_HIDDEN_ITEMS: &str = "<Meta>I";
 #[cfg(windows)]
 static TOGGLE_HIDDEN_ITEMS: &str = "<Alt>I";
 
-static HOME: &str = "Home";
-static BACK: &str = "BackSpace";
+#[cfg(unix)]
+static SORT_BY: [&str; 7] = [
+    "<Meta>1", "<Meta>2", "<Meta>3", "<Meta>4", "<Meta>5", "<Meta>6", "<Meta>7",
+];
+#[cfg(windows)]
+static SORT_BY: [&str; 7] = [
+    "<Alt>1", "<Alt>2", "<Alt>3", "<Alt>4", "<Alt>5", "<Alt>6", "<Alt>7",
+];
 
-pub fn setup_shortcuts(app: &Application, sender: &ComponentSender<App>) {
-    let close = ActionEntry::builder("quit")
-        .activate(|a: &Application, _, _| a.quit())
+#[cfg(unix)]
+static SORT_OPTIONS: [&str; 4] = [
+    "<Meta><Shift>R",
+    "<Meta><Shift>F",
+    "<Meta><Shift>N",
+    "<Meta><Shift>C",
+];
+#[cfg(windows)]
+static SORT_OPTIONS: [&str; 4] = [
+    "<Alt><Shift>R",
+    "<Alt><Shift>F",
+    "<Alt><Shift>N",
+    "<Alt><Shift>C",
+];
+
+static ZOOM_IN: [&str; 3] = ["<Control>plus", "<Control>equal", "<Control>KP_Add"];
+static ZOOM_OUT: [&str; 2] = ["<Control>minus", "<Control>KP_Subtract"];
+
+static HOME: &str = "<Alt>Home";
+static BACK: &str = "<Alt>Left";
+static FORWARD: &str = "<Alt>Right";
+static UP: &str = "<Alt>Up";
+static EDIT_LOCATION: &str = "<Control>L";
+static BOOKMARK: &str = "<Control>D";
+
+static NEW_WINDOW: &str = "<Control>N";
+static NEW_TAB: &str = "<Control>T";
+static NEW_FOLDER: &str = "<Control><Shift>N";
+static CLOSE_TAB: &str = "<Control>W";
+static NEXT_TAB: &str = "<Control>Tab";
+static PREVIOUS_TAB: [&str; 2] = ["<Control><Shift>Tab", "<Control><Shift>ISO_Left_Tab"];
+
+static SHOW_SIDEBAR: &str = "F9";
+static SPLIT: &str = "F3";
+static COPY_TO_OTHER_PANE: &str = "F5";
+static MOVE_TO_OTHER_PANE: &str = "F6";
+static RENAME: &str = "F2";
+#[cfg(unix)]
+static SYNC_PANES: &str = "<Alt>I";
+#[cfg(windows)]
+static SYNC_PANES: &str = "<Control><Shift>I";
+
+// Plain keys and the clipboard keys would be taken from text entries as app
+// accelerators, so they are only bound on the items box.
+static ITEMS_HOME: &str = "Home";
+static ITEMS_BACK: &str = "BackSpace";
+static ITEMS_SWITCH_PANE: &str = "Tab";
+pub static ITEMS_COPY: &str = "<Control>C";
+pub static ITEMS_CUT: &str = "<Control>X";
+pub static ITEMS_PASTE: &str = "<Control>V";
+pub static ITEMS_TRASH: &str = "Delete";
+pub static ITEMS_DELETE: &str = "<Shift>Delete";
+pub static ITEMS_UNDO: &str = "<Control>Z";
+pub static ITEMS_REDO: &str = "<Control><Shift>Z";
+
+/// The actions that need a second pane; they are disabled without one.
+pub static PANE_ACTIONS: [&str; 4] = [
+    "switch_pane",
+    "sync_panes",
+    "copy_to_other_pane",
+    "move_to_other_pane",
+];
+
+/// Adds the actions of `window`.
+pub fn setup_shortcuts(window: &ApplicationWindow, sender: &ComponentSender<App>) {
+    let new_window = ActionEntry::builder("new_window")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::NewWindow);
+            }
+        })
         .build();
 
     let show_hidden = ActionEntry::builder("show_hidden")
         .activate({
             let sender_clone = sender.clone();
-            move |_: &Application, _, _| {
+            move |_: &ApplicationWindow, _, _| {
                 sender_clone.input(AppInput::ToggleShowHiddenItems);
             }
         })
@@ -33,24 +115,519 @@ pub fn setup_shortcuts(app: &Application, sender: &ComponentSender<App>) {
     let home = ActionEntry::builder("home")
         .activate({
             let sender_clone = sender.clone();
-            move |_: &Application, _, _| {
+            move |_: &ApplicationWindow, _, _| {
                 sender_clone.input(AppInput::Home);
             }
         })
         .build();
 
     let back = ActionEntry::builder("back")
+        .parameter_type(Some(VariantTy::UINT32))
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, parameter: Option<&Variant>| {
+                let steps = parameter.and_then(Variant::get::<u32>).unwrap_or(1);
+                sender_clone.input(AppInput::Back(steps as usize));
+            }
+        })
+        .build();
+
+    let forward = ActionEntry::builder("forward")
+        .parameter_type(Some(VariantTy::UINT32))
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, parameter: Option<&Variant>| {
+                let steps = parameter.and_then(Variant::get::<u32>).unwrap_or(1);
+                sender_clone.input(AppInput::Forward(steps as usize));
+            }
+        })
+        .build();
+
+    let up = ActionEntry::builder("up")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::Up);
+            }
+        })
+        .build();
+
+    let go_to = ActionEntry::builder("go_to")
+        .parameter_type(Some(VariantTy::STRING))
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, parameter: Option<&Variant>| {
+                if let Some(path) = parameter.and_then(Variant::str) {
+                    sender_clone.input(AppInput::Navigate(path.to_owned()));
+                }
+            }
+        })
+        .build();
+
+    let bookmark = ActionEntry::builder("bookmark")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::BookmarkCurrentDirectory);
+            }
+        })
+        .build();
+
+    let edit_location = ActionEntry::builder("edit_location")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::EditLocation);
+            }
+        })
+        .build();
+
+    let new_tab = ActionEntry::builder("new_tab")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::NewTab);
+            }
+        })
+        .build();
+
+    let close_tab = ActionEntry::builder("close_tab")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::CloseTab);
+            }
+        })
+        .build();
+
+    let next_tab = ActionEntry::builder("next_tab")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::NextTab);
+            }
+        })
+        .build();
+
+    let previous_tab = ActionEntry::builder("previous_tab")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::PreviousTab);
+            }
+        })
+        .build();
+
+    let split = ActionEntry::builder("split")
+        .state(false.to_variant())
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::ToggleSplit);
+            }
+        })
+        .build();
+
+    let show_sidebar = ActionEntry::builder("show_sidebar")
+        .state(true.to_variant())
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, action: &SimpleAction, _| {
+                let show = !toggle_state(action);
+                action.set_state(&show.to_variant());
+                sender_clone.input(AppInput::ShowSidebar(show));
+            }
+        })
+        .build();
+
+    let switch_pane = ActionEntry::builder("switch_pane")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::SwitchPane);
+            }
+        })
+        .build();
+
+    let sync_panes = ActionEntry::builder("sync_panes")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::SyncPanes);
+            }
+        })
+        .build();
+
+    let copy_to_other_pane = ActionEntry::builder("copy_to_other_pane")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::TransferToOtherPane(TransferMode::Copy));
+            }
+        })
+        .build();
+
+    let move_to_other_pane = ActionEntry::builder("move_to_other_pane")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::TransferToOtherPane(TransferMode::Move));
+            }
+        })
+        .build();
+
+    let copy = ActionEntry::builder("copy")
         .activate({
             let sender_clone = sender.clone();
-            move |_: &Application, _, _| {
-                sender_clone.input(AppInput::Back);
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::CopyToClipboard(TransferMode::Copy));
             }
         })
         .build();
 
-    app.add_action_entries([close, show_hidden, home, back]);
+    let cut = ActionEntry::builder("cut")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::CopyToClipboard(TransferMode::Move));
+            }
+        })
+        .build();
+
+    let paste = ActionEntry::builder("paste")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::Paste);
+            }
+        })
+        .build();
+
+    let trash = ActionEntry::builder("trash")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::TrashSelection);
+            }
+        })
+        .build();
+
+    let delete = ActionEntry::builder("delete")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::DeleteSelection);
+            }
+        })
+        .build();
+
+    let new_folder = ActionEntry::builder("new_folder")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::Create(NewEntry::Folder));
+            }
+        })
+        .build();
+
+    let new_file = ActionEntry::builder("new_file")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::Create(NewEntry::File));
+            }
+        })
+        .build();
+
+    let new_document = ActionEntry::builder("new_document")
+        .parameter_type(Some(VariantTy::STRING))
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, parameter: Option<&Variant>| {
+                if let Some(template) = parameter.and_then(Variant::str) {
+                    let entry = NewEntry::Document(template.into());
+                    sender_clone.input(AppInput::Create(entry));
+                }
+            }
+        })
+        .build();
+
+    let undo = ActionEntry::builder("undo")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::Undo);
+            }
+        })
+        .build();
+
+    let redo = ActionEntry::builder("redo")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::Redo);
+            }
+        })
+        .build();
+
+    let history = ActionEntry::builder("history")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::ShowHistory);
+            }
+        })
+        .build();
+
+    let rename = ActionEntry::builder("rename")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::StartRename);
+            }
+        })
+        .build();
+
+    let view_mode = ActionEntry::builder("view_mode")
+        .parameter_type(Some(VariantTy::STRING))
+        .state(ViewMode::List.id().to_variant())
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, action: &SimpleAction, parameter: Option<&Variant>| {
+                let Some(id) = parameter.and_then(Variant::str) else {
+                    return;
+                };
+                if let Some(view_mode) = ViewMode::from_id(id) {
+                    action.set_state(&id.to_variant());
+                    sender_clone.input(AppInput::SetViewMode(view_mode));
+                }
+            }
+        })
+        .build();
+
+    let zoom_in = ActionEntry::builder("zoom_in")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::ZoomIn);
+            }
+        })
+        .build();
+
+    let zoom_out = ActionEntry::builder("zoom_out")
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, _, _| {
+                sender_clone.input(AppInput::ZoomOut);
+            }
+        })
+        .build();
+
+    let sort_by = ActionEntry::builder("sort_by")
+        .parameter_type(Some(VariantTy::STRING))
+        .state(Sorting::default().key.id().to_variant())
+        .activate({
+            let sender_clone = sender.clone();
+            move |_: &ApplicationWindow, action: &SimpleAction, parameter: Option<&Variant>| {
+                let Some(id) = parameter.and_then(Variant::str) else {
+                    return;
+                };
+                if let Some(key) = SortKey::from_id(id) {
+                    action.set_state(&id.to_variant());
+                    sender_clone.input(AppInput::SortBy(key));
+                }
+            }
+        })
+        .build();
+
+    let sort_options = SortOption::ALL.into_iter().map(|option| {
+        ActionEntry::builder(&sort_option_action(option))
+            .state(Sorting::default().option(option).to_variant())
+            .activate({
+                let sender_clone = sender.clone();
+                move |_: &ApplicationWindow, action: &SimpleAction, _| {
+                    let value = !toggle_state(action);
+                    action.set_state(&value.to_variant());
+                    sender_clone.input(AppInput::SetSortOption(option, value));
+                }
+            })
+            .build()
+    });
+
+    let columns = Column::ALL
+        .into_iter()
+        .filter(|column| *column != Column::Name)
+        .map(|column| {
+            ActionEntry::builder(&column_action(column))
+                .state(true.to_variant())
+                .activate({
+                    let sender_clone = sender.clone();
+                    move |_: &ApplicationWindow, action: &SimpleAction, _| {
+                        let visible = !toggle_state(action);
+                        action.set_state(&visible.to_variant());
+                        sender_clone.input(AppInput::SetColumnVisible(column, visible));
+                    }
+                })
+                .build()
+        });
+
+    window.add_action_entries([
+        new_window,
+        show_hidden,
+        home,
+        back,
+        forward,
+        up,
+        go_to,
+        bookmark,
+        edit_location,
+        new_tab,
+        close_tab,
+        next_tab,
+        previous_tab,
+        split,
+        show_sidebar,
+        switch_pane,
+        sync_panes,
+        copy_to_other_pane,
+        move_to_other_pane,
+        copy,
+        cut,
+        paste,
+        trash,
+        delete,
+        undo,
+        redo,
+        history,
+        rename,
+        new_folder,
+        new_file,
+        new_document,
+        view_mode,
+        zoom_in,
+        zoom_out,
+        sort_by,
+    ]);
+    window.add_action_entries(sort_options);
+    window.add_action_entries(columns);
+    for action in PANE_ACTIONS {
+        set_action_enabled(window, action, false);
+    }
+}
+
+/// Adds the quit action and the shortcuts of the application, which are shared by
+/// all of its windows.
+pub fn setup_accels(app: &Application) {
+    let close = ActionEntry::builder("quit")
+        .activate(|a: &Application, _, _| a.quit())
+        .build();
+    app.add_action_entries([close]);
+
     app.set_accels_for_action("app.quit", &[CLOSE_APP]);
-    app.set_accels_for_action("app.show_hidden", &[TOGGLE_HIDDEN_ITEMS]);
-    app.set_accels_for_action("app.home", &[HOME]);
-    app.set_accels_for_action("app.back", &[BACK]);
+    app.set_accels_for_action("win.new_window", &[NEW_WINDOW]);
+    app.set_accels_for_action("win.show_hidden", &[TOGGLE_HIDDEN_ITEMS]);
+    app.set_accels_for_action("win.home", &[HOME]);
+    app.set_accels_for_action(&history_action("back", 1), &[BACK]);
+    app.set_accels_for_action(&history_action("forward", 1), &[FORWARD]);
+    app.set_accels_for_action("win.up", &[UP]);
+    app.set_accels_for_action("win.edit_location", &[EDIT_LOCATION]);
+    app.set_accels_for_action("win.bookmark", &[BOOKMARK]);
+    app.set_accels_for_action("win.new_tab", &[NEW_TAB]);
+    app.set_accels_for_action("win.new_folder", &[NEW_FOLDER]);
+    app.set_accels_for_action("win.close_tab", &[CLOSE_TAB]);
+    app.set_accels_for_action("win.next_tab", &[NEXT_TAB]);
+    app.set_accels_for_action("win.previous_tab", &PREVIOUS_TAB);
+    app.set_accels_for_action("win.show_sidebar", &[SHOW_SIDEBAR]);
+    app.set_accels_for_action("win.split", &[SPLIT]);
+    app.set_accels_for_action("win.sync_panes", &[SYNC_PANES]);
+    app.set_accels_for_action("win.copy_to_other_pane", &[COPY_TO_OTHER_PANE]);
+    app.set_accels_for_action("win.move_to_other_pane", &[MOVE_TO_OTHER_PANE]);
+    app.set_accels_for_action("win.rename", &[RENAME]);
+    app.set_accels_for_action("win.zoom_in", &ZOOM_IN);
+    app.set_accels_for_action("win.zoom_out", &ZOOM_OUT);
+    for (key, accel) in SortKey::ALL.into_iter().zip(SORT_BY) {
+        app.set_accels_for_action(&format!("win.sort_by::{}", key.id()), &[accel]);
+    }
+    for (option, accel) in SortOption::ALL.into_iter().zip(SORT_OPTIONS) {
+        app.set_accels_for_action(&format!("win.{}", sort_option_action(option)), &[accel]);
+    }
+}
+
+/// The Home, Backspace, Tab, Delete, undo and clipboard shortcuts of the items box; Tab only
+/// switches panes while the window is split, since a disabled action lets the key through.
+pub fn items_shortcuts() -> gtk::ShortcutController {
+    let controller = gtk::ShortcutController::new();
+    controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+    for (trigger, action, steps) in [
+        (ITEMS_HOME, "home", None),
+        (ITEMS_BACK, "back", Some(1u32)),
+        (ITEMS_SWITCH_PANE, "switch_pane", None),
+        (ITEMS_COPY, "copy", None),
+        (ITEMS_CUT, "cut", None),
+        (ITEMS_PASTE, "paste", None),
+        (ITEMS_TRASH, "trash", None),
+        (ITEMS_DELETE, "delete", None),
+        (ITEMS_UNDO, "undo", None),
+        (ITEMS_REDO, "redo", None),
+    ] {
+        let shortcut = gtk::Shortcut::new(
+            gtk::ShortcutTrigger::parse_string(trigger),
+            Some(items_action(action)),
+        );
+        shortcut.set_arguments(steps.map(|steps| steps.to_variant()).as_ref());
+        controller.add_shortcut(shortcut);
+    }
+    controller
+}
+
+/// Activates a window action, unless it is disabled or a text field has the focus, in which
+/// case the key goes on to the focused widget.
+fn items_action(name: &'static str) -> gtk::CallbackAction {
+    gtk::CallbackAction::new(move |widget, arguments| {
+        let editing = widget
+            .root()
+            .and_then(|root| root.focus())
+            .is_some_and(|focus| focus.is::<gtk::Editable>());
+        let enabled = widget
+            .root()
+            .and_downcast::<ApplicationWindow>()
+            .is_some_and(|window| window.is_action_enabled(name));
+        if editing || !enabled {
+            return glib::Propagation::Proceed;
+        }
+        let _ = WidgetExt::activate_action(widget, &format!("win.{name}"), arguments);
+        glib::Propagation::Stop
+    })
+}
+
+/// The detailed name of `win.back` or `win.forward` moving the given number of steps.
+pub fn history_action(name: &str, steps: u32) -> String {
+    format!("win.{name}({})", steps.to_variant().print(true))
+}
+
+pub fn column_action(column: Column) -> String {
+    format!("column_{}", column.id())
+}
+
+pub fn sort_option_action(option: SortOption) -> String {
+    format!("sort_{}", option.id())
+}
+
+pub fn set_action_state(window: &ApplicationWindow, name: &str, state: Variant) {
+    if let Some(action) = window.lookup_action(name).and_downcast::<SimpleAction>() {
+        action.set_state(&state);
+    }
+}
+
+pub fn set_action_enabled(window: &ApplicationWindow, name: &str, enabled: bool) {
+    if let Some(action) = window.lookup_action(name).and_downcast::<SimpleAction>() {
+        action.set_enabled(enabled);
+    }
+}
+
+fn toggle_state(action: &SimpleAction) -> bool {
+    action
+        .state()
+        .and_then(|state| state.get::<bool>())
+        .unwrap_or_default()
 }
@@ -0,0 +1,15 @@
+use crate::core::DirEntryInfo;
+
+pub struct BatchRenameInit {
+    _entries: Vec<DirEntryInfo>,
+}
+
+impl BatchRenameInit {
+    pub fn new(entries: Vec<DirEntryInfo>) -> Self {
+        Self { _entries: entries }
+    }
+
+    pub fn entries(&self) -> &[DirEntryInfo] {
+        &self._entries
+    }
+}
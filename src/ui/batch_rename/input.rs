@@ -0,0 +1,13 @@
+use crate::core::Case;
+
+#[derive(Debug)]
+pub enum BatchRenameInput {
+    SetFind(String),
+    SetReplacement(String),
+    SetRegex(bool),
+    SetTemplate(String),
+    SetStart(u64),
+    SetCase(Case),
+    Apply,
+    Cancel,
+}
@@ -0,0 +1,345 @@
+mod init;
+mod input;
+mod output;
+
+use std::path::PathBuf;
+
+pub use init::BatchRenameInit;
+pub(crate) use input::BatchRenameInput;
+pub(crate) use output::BatchRenameOutput;
+use relm4::gtk::{gdk, glib, prelude::*};
+use relm4::prelude::*;
+
+use crate::core::{
+    Case, DirEntryInfo, LocalTime, RenameConflict, RenameItem, RenamePattern, rename_conflicts,
+};
+
+const TOKENS_HINT: &str = "Use {name}, {ext}, {n}, {n:3}, {date}, {today} or {date:%Y%m%d} \
+    in the new name, and \\1 for the groups of a regular expression.";
+
+fn preview_row(
+    old_name: &str,
+    new_name: &str,
+    conflict: Option<RenameConflict>,
+) -> gtk::ListBoxRow {
+    let old_label = gtk::Label::new(Some(old_name));
+    let arrow = gtk::Label::new(Some("→"));
+    arrow.add_css_class("dim-label");
+    let new_label = gtk::Label::new(Some(new_name));
+    if old_name == new_name {
+        new_label.add_css_class("dim-label");
+    }
+
+    let row_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    row_box.add_css_class("rename-preview");
+    for label in [&old_label, &new_label] {
+        label.set_xalign(0.0);
+        label.set_hexpand(true);
+        label.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+    }
+    row_box.append(&old_label);
+    row_box.append(&arrow);
+    row_box.append(&new_label);
+    if let Some(conflict) = conflict {
+        let conflict = gtk::Label::new(Some(conflict.description()));
+        conflict.add_css_class("error");
+        row_box.append(&conflict);
+    }
+
+    let row = gtk::ListBoxRow::new();
+    row.set_child(Some(&row_box));
+    row
+}
+
+/// Renames several items at once, previewing the new names as the pattern is edited.
+#[tracker::track]
+pub struct BatchRename {
+    #[tracker::do_not_track]
+    entries: Vec<DirEntryInfo>,
+    #[tracker::do_not_track]
+    find: String,
+    #[tracker::do_not_track]
+    replacement: String,
+    #[tracker::do_not_track]
+    regex: bool,
+    #[tracker::do_not_track]
+    pattern: RenamePattern,
+    #[tracker::do_not_track]
+    preview: gtk::ListBox,
+    /// The items whose name changes, with their new names.
+    #[tracker::do_not_track]
+    renames: Vec<(PathBuf, String)>,
+    can_apply: bool,
+    error: String,
+}
+
+impl BatchRename {
+    pub fn init(entries: Vec<DirEntryInfo>) -> BatchRenameInit {
+        BatchRenameInit::new(entries)
+    }
+
+    /// The new name of each entry, or the error of the regular expression. Names that are not
+    /// UTF-8 keep their name, as the text of the pattern would lose their bytes.
+    fn new_names(&self) -> Result<Vec<String>, String> {
+        let regex = match self.regex && !self.find.is_empty() {
+            true => glib::Regex::new(
+                &self.find,
+                glib::RegexCompileFlags::empty(),
+                glib::RegexMatchFlags::empty(),
+            )
+            .map_err(|error| error.to_string())?,
+            false => None,
+        };
+        let today = LocalTime::now();
+
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let Some(name) = entry.name().to_str() else {
+                    return Ok(entry.name().to_string_lossy().into_owned());
+                };
+                let replaced = match &regex {
+                    Some(regex) => regex
+                        .replace(name, 0, &*self.replacement, glib::RegexMatchFlags::empty())
+                        .map_err(|error| error.to_string())?
+                        .to_string(),
+                    None if !self.find.is_empty() => name.replace(&self.find, &self.replacement),
+                    None => name.to_owned(),
+                };
+                let item = RenameItem {
+                    name: &replaced,
+                    is_dir: entry.is_dir(),
+                    modified: entry.modified().map(LocalTime::from_system_time),
+                };
+                Ok(self.pattern.new_name(&item, index, today))
+            })
+            .collect()
+    }
+
+    fn update_preview(&mut self) {
+        while let Some(row) = self.preview.row_at_index(0) {
+            self.preview.remove(&row);
+        }
+        self.renames.clear();
+        let names = match self.new_names() {
+            Ok(names) => names,
+            Err(error) => {
+                self.set_error(error);
+                self.set_can_apply(false);
+                return;
+            }
+        };
+        self.set_error(String::new());
+
+        let renames: Vec<(PathBuf, String)> = self
+            .entries
+            .iter()
+            .zip(names)
+            .map(|(entry, name)| (entry.path().to_path_buf(), name))
+            .collect();
+        let conflicts: Vec<_> = rename_conflicts(&renames)
+            .into_iter()
+            .zip(&self.entries)
+            .map(|(conflict, entry)| conflict.filter(|_| entry.name().to_str().is_some()))
+            .collect();
+        for ((entry, (_, name)), conflict) in self.entries.iter().zip(&renames).zip(&conflicts) {
+            let old_name = entry.name().to_string_lossy();
+            self.preview
+                .append(&preview_row(&old_name, name, *conflict));
+        }
+
+        self.renames = self
+            .entries
+            .iter()
+            .zip(renames)
+            .filter(|(entry, (_, name))| entry.name().to_str().is_some_and(|old| old != name))
+            .map(|(_, rename)| rename)
+            .collect();
+        self.set_can_apply(!self.renames.is_empty() && conflicts.iter().all(Option::is_none));
+    }
+}
+
+#[relm4::component(pub)]
+impl Component for BatchRename {
+    type Init = BatchRenameInit;
+    type Input = BatchRenameInput;
+    type Output = BatchRenameOutput;
+    type CommandOutput = ();
+
+    view! {
+        gtk::Window {
+            set_title: Some(&title),
+            set_modal: true,
+            set_default_width: 640,
+            set_default_height: 560,
+            add_controller = gtk::EventControllerKey {
+                connect_key_pressed[sender] => move |_, key, _, _| {
+                    if key != gdk::Key::Escape {
+                        return glib::Propagation::Proceed;
+                    }
+                    sender.input(BatchRenameInput::Cancel);
+                    glib::Propagation::Stop
+                },
+            },
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 12,
+                set_css_classes: &["batch-rename"],
+
+                gtk::Grid {
+                    set_row_spacing: 6,
+                    set_column_spacing: 12,
+
+                    attach[0, 0, 1, 1] = &gtk::Label {
+                        set_label: "Find",
+                        set_xalign: 1.0,
+                    },
+                    attach[1, 0, 1, 1] = &gtk::Entry {
+                        set_hexpand: true,
+                        connect_changed[sender] => move |entry| {
+                            sender.input(BatchRenameInput::SetFind(entry.text().into()));
+                        },
+                    },
+                    attach[2, 0, 1, 1] = &gtk::CheckButton {
+                        set_label: Some("Regular expression"),
+                        connect_toggled[sender] => move |button| {
+                            sender.input(BatchRenameInput::SetRegex(button.is_active()));
+                        },
+                    },
+                    attach[0, 1, 1, 1] = &gtk::Label {
+                        set_label: "Replace with",
+                        set_xalign: 1.0,
+                    },
+                    attach[1, 1, 2, 1] = &gtk::Entry {
+                        connect_changed[sender] => move |entry| {
+                            let replacement = entry.text().into();
+                            sender.input(BatchRenameInput::SetReplacement(replacement));
+                        },
+                    },
+                    attach[0, 2, 1, 1] = &gtk::Label {
+                        set_label: "New name",
+                        set_xalign: 1.0,
+                    },
+                    attach[1, 2, 2, 1] = &gtk::Entry {
+                        set_text: &model.pattern.template,
+                        connect_changed[sender] => move |entry| {
+                            sender.input(BatchRenameInput::SetTemplate(entry.text().into()));
+                        },
+                    },
+                    attach[0, 3, 1, 1] = &gtk::Label {
+                        set_label: "Numbers from",
+                        set_xalign: 1.0,
+                    },
+                    attach[1, 3, 1, 1] = &gtk::SpinButton::with_range(0.0, 1_000_000.0, 1.0) {
+                        set_halign: gtk::Align::Start,
+                        set_value: model.pattern.start as f64,
+                        connect_value_changed[sender] => move |button| {
+                            sender.input(BatchRenameInput::SetStart(button.value() as u64));
+                        },
+                    },
+                    attach[2, 3, 1, 1] = &gtk::DropDown::from_strings(&Case::ALL.map(|case| case.title())) {
+                        connect_selected_notify[sender] => move |drop_down| {
+                            if let Some(case) = Case::ALL.get(drop_down.selected() as usize) {
+                                sender.input(BatchRenameInput::SetCase(*case));
+                            }
+                        },
+                    },
+                },
+                gtk::Label {
+                    set_label: TOKENS_HINT,
+                    set_wrap: true,
+                    set_xalign: 0.0,
+                    set_css_classes: &["dim-label", "caption"],
+                },
+                gtk::Label {
+                    set_xalign: 0.0,
+                    set_css_classes: &["error"],
+                    #[track = "model.changed(BatchRename::error())"]
+                    set_label: &model.error,
+                    #[track = "model.changed(BatchRename::error())"]
+                    set_visible: !model.error.is_empty(),
+                },
+                gtk::ScrolledWindow {
+                    set_vexpand: true,
+                    set_hscrollbar_policy: gtk::PolicyType::Never,
+
+                    #[local_ref]
+                    preview -> gtk::ListBox {
+                        set_selection_mode: gtk::SelectionMode::None,
+                    },
+                },
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_spacing: 6,
+                    set_halign: gtk::Align::End,
+
+                    gtk::Button {
+                        set_label: "Cancel",
+                        connect_clicked[sender] => move |_| {
+                            sender.input(BatchRenameInput::Cancel);
+                        },
+                    },
+                    gtk::Button {
+                        set_label: "Rename",
+                        set_css_classes: &["suggested-action"],
+                        #[track = "model.changed(BatchRename::can_apply())"]
+                        set_sensitive: model.can_apply,
+                        connect_clicked[sender] => move |_| {
+                            sender.input(BatchRenameInput::Apply);
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let mut model = Self {
+            entries: init.entries().to_vec(),
+            find: String::new(),
+            replacement: String::new(),
+            regex: false,
+            pattern: RenamePattern::default(),
+            preview: gtk::ListBox::new(),
+            renames: Vec::new(),
+            can_apply: false,
+            error: String::new(),
+            tracker: 0,
+        };
+        model.update_preview();
+
+        let title = format!("Rename {} Items", model.entries.len());
+        let preview = &model.preview;
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        self.reset();
+        match message {
+            BatchRenameInput::SetFind(find) => self.find = find,
+            BatchRenameInput::SetReplacement(replacement) => self.replacement = replacement,
+            BatchRenameInput::SetRegex(regex) => self.regex = regex,
+            BatchRenameInput::SetTemplate(template) => self.pattern.template = template,
+            BatchRenameInput::SetStart(start) => self.pattern.start = start,
+            BatchRenameInput::SetCase(case) => self.pattern.case = case,
+            BatchRenameInput::Apply => {
+                let (paths, names) = std::mem::take(&mut self.renames).into_iter().unzip();
+                let _ = sender.output(BatchRenameOutput::Rename(paths, names));
+                root.close();
+                return;
+            }
+            BatchRenameInput::Cancel => {
+                root.close();
+                return;
+            }
+        }
+        self.update_preview();
+    }
+}
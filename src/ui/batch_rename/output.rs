@@ -0,0 +1,7 @@
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum BatchRenameOutput {
+    /// The items that get a new name, with their new names.
+    Rename(Vec<PathBuf>, Vec<String>),
+}
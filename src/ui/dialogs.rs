@@ -0,0 +1,49 @@
+use relm4::gtk::{self, gio, prelude::*};
+
+/// How many errors a report lists before summing up the rest.
+const REPORTED_ERRORS: usize = 10;
+
+/// Asks before something is done that cannot be undone, like deleting files,
+/// and calls `accepted` if the user agrees.
+pub fn confirm(
+    parent: &impl IsA<gtk::Widget>,
+    message: &str,
+    detail: &str,
+    accept_label: &str,
+    accepted: impl FnOnce() + 'static,
+) {
+    let dialog = gtk::AlertDialog::builder()
+        .modal(true)
+        .message(message)
+        .detail(detail)
+        .buttons(["Cancel", accept_label])
+        .cancel_button(0)
+        .default_button(0)
+        .build();
+    let window = parent.root().and_downcast::<gtk::Window>();
+    dialog.choose(window.as_ref(), gio::Cancellable::NONE, move |response| {
+        if response == Ok(1) {
+            accepted();
+        }
+    });
+}
+
+/// Lists the errors of an operation that went on past them.
+pub fn report_errors(parent: &impl IsA<gtk::Widget>, message: &str, errors: &[String]) {
+    let mut detail = errors
+        .iter()
+        .take(REPORTED_ERRORS)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if errors.len() > REPORTED_ERRORS {
+        detail.push_str(&format!("\n…and {} more", errors.len() - REPORTED_ERRORS));
+    }
+    let dialog = gtk::AlertDialog::builder()
+        .modal(true)
+        .message(message)
+        .detail(detail)
+        .build();
+    let window = parent.root().and_downcast::<gtk::Window>();
+    dialog.show(window.as_ref());
+}
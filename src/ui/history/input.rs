@@ -0,0 +1,8 @@
+use crate::core::JournalEntry;
+
+#[derive(Debug)]
+pub enum HistoryInput {
+    /// Shows the entries of the journal, oldest first, and whether an operation
+    /// can be undone and redone.
+    Update(Vec<JournalEntry>, bool, bool),
+}
@@ -0,0 +1,148 @@
+mod input;
+mod output;
+
+pub(crate) use input::HistoryInput;
+pub(crate) use output::HistoryOutput;
+use relm4::gtk::prelude::*;
+use relm4::prelude::*;
+
+use crate::core::{JournalEntry, Outcome};
+
+fn entry_row(entry: &JournalEntry) -> gtk::ListBoxRow {
+    let time = gtk::Label::new(Some(&entry.time.format("%H:%M:%S")));
+    time.add_css_class("dim-label");
+
+    let description = gtk::Label::new(Some(&entry.operation.description()));
+    description.set_hexpand(true);
+    description.set_xalign(0.0);
+    description.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+
+    let outcome = gtk::Label::new(Some(&entry.outcome.title()));
+    match entry.outcome {
+        Outcome::Failed(_) => outcome.add_css_class("error"),
+        Outcome::Cancelled | Outcome::Undone => outcome.add_css_class("dim-label"),
+        Outcome::Running | Outcome::Done => {}
+    }
+
+    let content = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    content.add_css_class("history-entry");
+    content.append(&time);
+    content.append(&description);
+    content.append(&outcome);
+    let row = gtk::ListBoxRow::new();
+    row.set_child(Some(&content));
+    row
+}
+
+/// The recent file operations of a window, newest first, with what came of them.
+#[tracker::track]
+pub struct History {
+    #[tracker::do_not_track]
+    list: gtk::ListBox,
+    has_entries: bool,
+    can_undo: bool,
+    can_redo: bool,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for History {
+    type Init = ();
+    type Input = HistoryInput;
+    type Output = HistoryOutput;
+
+    view! {
+        gtk::Window {
+            set_title: Some("History"),
+            set_hide_on_close: true,
+            set_destroy_with_parent: true,
+            set_default_width: 560,
+            set_default_height: 420,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_spacing: 6,
+                    set_css_classes: &["history-header"],
+
+                    gtk::Label {
+                        set_label: "Recent Operations",
+                        set_hexpand: true,
+                        set_xalign: 0.0,
+                        set_css_classes: &["title-4"],
+                    },
+                    gtk::Button {
+                        set_label: "Undo",
+                        #[track = "model.changed(History::can_undo())"]
+                        set_sensitive: model.can_undo,
+                        connect_clicked[sender] => move |_| {
+                            let _ = sender.output(HistoryOutput::Undo);
+                        },
+                    },
+                    gtk::Button {
+                        set_label: "Redo",
+                        #[track = "model.changed(History::can_redo())"]
+                        set_sensitive: model.can_redo,
+                        connect_clicked[sender] => move |_| {
+                            let _ = sender.output(HistoryOutput::Redo);
+                        },
+                    },
+                },
+                gtk::ScrolledWindow {
+                    set_vexpand: true,
+                    set_hscrollbar_policy: gtk::PolicyType::Never,
+                    #[track = "model.changed(History::has_entries())"]
+                    set_visible: model.has_entries,
+
+                    #[local_ref]
+                    list -> gtk::ListBox {
+                        set_css_classes: &["items-list"],
+                        set_selection_mode: gtk::SelectionMode::None,
+                    },
+                },
+                gtk::Label {
+                    set_label: "No file operations yet",
+                    set_vexpand: true,
+                    set_css_classes: &["dim-label", "title-2"],
+                    #[track = "model.changed(History::has_entries())"]
+                    set_visible: !model.has_entries,
+                },
+            },
+        }
+    }
+
+    fn init(
+        _: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            list: gtk::ListBox::new(),
+            has_entries: false,
+            can_undo: false,
+            can_redo: false,
+            tracker: 0,
+        };
+        let list = &model.list;
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, _: ComponentSender<Self>) {
+        self.reset();
+        match message {
+            HistoryInput::Update(entries, can_undo, can_redo) => {
+                while let Some(row) = self.list.row_at_index(0) {
+                    self.list.remove(&row);
+                }
+                for entry in entries.iter().rev() {
+                    self.list.append(&entry_row(entry));
+                }
+                self.set_has_entries(!entries.is_empty());
+                self.set_can_undo(can_undo);
+                self.set_can_redo(can_redo);
+            }
+        }
+    }
+}
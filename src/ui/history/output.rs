@@ -0,0 +1,5 @@
+#[derive(Debug)]
+pub enum HistoryOutput {
+    Undo,
+    Redo,
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use relm4::gtk::{gdk, gio, glib, prelude::*};
+
+use crate::core::{
+    GNOME_COPIED_FILES, TransferMode, URI_LIST, format_gnome_copied_files, format_plain_paths,
+    format_uri_list, parse_gnome_copied_files, parse_uri_list,
+};
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Offers `paths` in the formats other file managers, editors and terminals read.
+pub fn set_files(
+    clipboard: &gdk::Clipboard,
+    mode: TransferMode,
+    paths: &[PathBuf],
+) -> Result<(), glib::BoolError> {
+    let for_text = |mime_type, content: String| {
+        gdk::ContentProvider::for_bytes(mime_type, &glib::Bytes::from_owned(content))
+    };
+    let files: Vec<gio::File> = paths.iter().map(gio::File::for_path).collect();
+    let provider = gdk::ContentProvider::new_union(&[
+        for_text(GNOME_COPIED_FILES, format_gnome_copied_files(mode, paths)),
+        for_text(URI_LIST, format_uri_list(paths)),
+        gdk::ContentProvider::for_value(&gdk::FileList::from_array(&files).to_value()),
+        gdk::ContentProvider::for_value(&format_plain_paths(paths).to_value()),
+    ]);
+    clipboard.set_content(Some(&provider))
+}
+
+pub fn clear(clipboard: &gdk::Clipboard) {
+    let _ = clipboard.set_content(None::<&gdk::ContentProvider>);
+}
+
+/// The files on the clipboard and whether they were cut, `None` without any.
+pub async fn read_files(clipboard: &gdk::Clipboard) -> Option<(TransferMode, Vec<PathBuf>)> {
+    let (stream, mime_type) = clipboard
+        .read_future(&[GNOME_COPIED_FILES, URI_LIST], glib::Priority::DEFAULT)
+        .await
+        .ok()?;
+
+    let mut content = Vec::new();
+    loop {
+        let bytes = stream
+            .read_bytes_future(READ_CHUNK_SIZE, glib::Priority::DEFAULT)
+            .await
+            .ok()?;
+        if bytes.is_empty() {
+            break;
+        }
+        content.extend_from_slice(&bytes);
+    }
+
+    let content = String::from_utf8_lossy(&content);
+    let (mode, paths) = match mime_type.as_str() {
+        GNOME_COPIED_FILES => parse_gnome_copied_files(&content)?,
+        _ => (TransferMode::Copy, parse_uri_list(&content)),
+    };
+    (!paths.is_empty()).then_some((mode, paths))
+}
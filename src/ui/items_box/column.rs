@@ -0,0 +1,76 @@
+use std::time::UNIX_EPOCH;
+
+use relm4::gtk::{gio, glib};
+
+use super::item::Item;
+use crate::core::user_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    Size,
+    Modified,
+    Type,
+    Permissions,
+    Owner,
+}
+
+impl Column {
+    pub const ALL: [Column; 6] = [
+        Column::Name,
+        Column::Size,
+        Column::Modified,
+        Column::Type,
+        Column::Permissions,
+        Column::Owner,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Size => "size",
+            Column::Modified => "modified",
+            Column::Type => "type",
+            Column::Permissions => "permissions",
+            Column::Owner => "owner",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|column| column.id() == id)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Size => "Size",
+            Column::Modified => "Modified",
+            Column::Type => "Type",
+            Column::Permissions => "Permissions",
+            Column::Owner => "Owner",
+        }
+    }
+
+    pub fn text(&self, item: &Item) -> String {
+        let entry = item.entry();
+        match self {
+            Column::Name => item.name().to_owned(),
+            Column::Size if entry.is_dir() => String::new(),
+            Column::Size => glib::format_size(entry.size()).into(),
+            Column::Modified => entry
+                .modified()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .and_then(|since_epoch| {
+                    glib::DateTime::from_unix_local(since_epoch.as_secs() as i64).ok()
+                })
+                .and_then(|date_time| date_time.format("%Y-%m-%d %H:%M").ok())
+                .map(Into::into)
+                .unwrap_or_default(),
+            Column::Type => gio::content_type_get_description(item.content_type()).into(),
+            Column::Permissions => entry.permissions_text(),
+            Column::Owner => user_name(entry.uid())
+                .map(str::to_owned)
+                .unwrap_or_else(|| entry.uid().to_string()),
+        }
+    }
+}
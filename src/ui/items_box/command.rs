@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use relm4::gtk::{gdk, glib};
+
+use crate::core::DirEntryInfo;
+
+#[derive(Debug)]
+pub enum ItemsBoxCommand {
+    DirectoryOpened(usize, String),
+    ItemsLoaded(usize, Vec<(DirEntryInfo, glib::GString)>),
+    ItemsChanged(usize, Vec<(PathBuf, Option<(DirEntryInfo, glib::GString)>)>),
+    LoadingFinished(usize),
+    LoadingFailed(usize, String),
+    ThumbnailLoaded(PathBuf, gdk::Texture),
+    /// The items to delete with the bytes and files below them, to be confirmed.
+    DeleteMeasured(Vec<PathBuf>, u64, u64),
+    /// The templates read for the context menu, opened at the given point.
+    TemplatesRead(Vec<PathBuf>, f64, f64),
+}
+
+impl ItemsBoxCommand {
+    /// The load the message belongs to, `None` for messages that outlive it.
+    pub fn load_id(&self) -> Option<usize> {
+        match self {
+            Self::DirectoryOpened(load_id, _)
+            | Self::ItemsLoaded(load_id, _)
+            | Self::ItemsChanged(load_id, _)
+            | Self::LoadingFinished(load_id)
+            | Self::LoadingFailed(load_id, _) => Some(*load_id),
+            Self::ThumbnailLoaded(..) | Self::DeleteMeasured(..) | Self::TemplatesRead(..) => None,
+        }
+    }
+}
@@ -1,10 +1,44 @@
-use relm4::factory::DynamicIndex;
+use std::path::PathBuf;
+
+use super::{Column, SortKey, SortOption, ViewMode, Zoom};
+use crate::core::{NewEntry, TransferMode};
 
 #[derive(Debug)]
 pub enum ItemsBoxInput {
     LoadDirectory(String),
+    Back(usize),
+    Forward(usize),
+    Up,
     ToggleShowHiddenItems,
     ShowHiddenItems(bool),
-    OpenFile(String),
-    SelectItem(DynamicIndex),
+    ActivateItem(u32),
+    OpenInNewTab(u32),
+    ItemsChanged(Vec<PathBuf>),
+    /// Selects the item with the given path once the directory is loaded.
+    Select(PathBuf),
+    /// Copies or moves the selected items into the given directory.
+    TransferSelection(TransferMode, String),
+    /// Puts the selected items on the clipboard, to be copied or moved when pasted.
+    CopyToClipboard(TransferMode),
+    /// Pastes the files on the clipboard into the current directory.
+    Paste,
+    PasteFiles(TransferMode, Vec<PathBuf>),
+    /// Moves the selected items to the trash.
+    TrashSelection,
+    /// Deletes the selected items for good once the user confirmed it.
+    DeleteSelection,
+    /// Renames the selected item in place, or several of them in a dialog.
+    StartRename,
+    Rename(Vec<PathBuf>, Vec<String>),
+    /// Creates a folder or file in the current directory and starts renaming it.
+    Create(NewEntry),
+    /// Opens the context menu at the given point of the items box.
+    ShowContextMenu(f64, f64),
+    SetViewMode(ViewMode),
+    GrabFocus,
+    SetZoom(Zoom),
+    SetColumnVisible(Column, bool),
+    SortBy(SortKey),
+    SetSortOption(SortOption, bool),
+    Sort(SortKey, bool),
 }
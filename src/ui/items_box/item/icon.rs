@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::Read;
+
+use relm4::gtk::{gio, glib, prelude::*};
+
+use crate::core::{DirEntryInfo, EntryType};
+
+const SNIFF_SIZE: usize = 4096;
+
+/// Guesses the MIME type from the file name and, when that is not conclusive,
+/// from the first bytes of the file. Reads from disk, so keep it off the main thread.
+pub fn content_type(entry: &DirEntryInfo) -> glib::GString {
+    let inode_type = match entry.target_type() {
+        None => Some("inode/symlink"),
+        Some(EntryType::Directory) => Some("inode/directory"),
+        Some(EntryType::BlockDevice) => Some("inode/blockdevice"),
+        Some(EntryType::CharDevice) => Some("inode/chardevice"),
+        Some(EntryType::Fifo) => Some("inode/fifo"),
+        Some(EntryType::Socket) => Some("inode/socket"),
+        Some(_) => None,
+    };
+    if let Some(inode_type) = inode_type {
+        return inode_type.into();
+    }
+
+    let (content_type, uncertain) = gio::content_type_guess(Some(entry.name()), None::<&[u8]>);
+    if !uncertain {
+        return content_type;
+    }
+
+    match sniff(entry) {
+        Some(data) if !data.is_empty() => gio::content_type_guess(Some(entry.name()), &data[..]).0,
+        _ => content_type,
+    }
+}
+
+fn sniff(entry: &DirEntryInfo) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(SNIFF_SIZE);
+    File::open(entry.path())
+        .ok()?
+        .take(SNIFF_SIZE as u64)
+        .read_to_end(&mut data)
+        .ok()?;
+    Some(data)
+}
+
+/// A themed icon for the entry; the bundled `folder` and `file` icons are the last
+/// fallbacks, used only when the icon theme has none of the other names.
+pub fn icon(entry: &DirEntryInfo, content_type: &str) -> gio::Icon {
+    let names: &[&str] = match entry.target_type() {
+        None => &["emblem-unreadable", "file"],
+        Some(EntryType::Directory) => &["folder"],
+        Some(EntryType::BlockDevice) => &["inode-blockdevice", "drive-harddisk", "file"],
+        Some(EntryType::CharDevice) => &["inode-chardevice", "input-keyboard", "file"],
+        Some(EntryType::Fifo) => &["inode-fifo", "file"],
+        Some(EntryType::Socket) => &["inode-socket", "network-wired", "file"],
+        Some(_) if is_executable(entry, content_type) => &["application-x-executable", "file"],
+        Some(_) => {
+            let icon = gio::content_type_get_icon(content_type);
+            if let Some(themed) = icon.downcast_ref::<gio::ThemedIcon>() {
+                themed.append_name("file");
+            }
+            return icon;
+        }
+    };
+    gio::ThemedIcon::from_names(names).upcast()
+}
+
+/// The emblem drawn over the icon of a symbolic link.
+pub fn emblem(entry: &DirEntryInfo) -> Option<&'static str> {
+    (entry.is_symlink() && !entry.is_broken_symlink()).then_some("emblem-symbolic-link")
+}
+
+fn is_executable(entry: &DirEntryInfo, content_type: &str) -> bool {
+    entry.entry_type() == EntryType::File
+        && entry.permissions() & 0o111 != 0
+        && gio::content_type_can_be_executable(content_type)
+}
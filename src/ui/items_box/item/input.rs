@@ -1,5 +0,0 @@
-#[derive(Debug)]
-pub enum ItemInput {
-    ItemRightClicked,
-    ItemLeftClicked,
-}
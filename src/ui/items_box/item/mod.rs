@@ -1,115 +1,281 @@
-mod init;
-mod input;
-mod output;
+mod icon;
 
-use std::path::PathBuf;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::ffi::CStr;
+use std::rc::Rc;
 
-use gtk::prelude::*;
+use relm4::gtk::{self, gdk, gio, glib, prelude::*};
 
-pub(crate) use init::ItemInit;
-pub(crate) use input::ItemInput;
-pub(crate) use output::ItemOutput;
-use relm4::prelude::*;
+use super::thumbnails::Thumbnails;
+use crate::core::DirEntryInfo;
+pub use icon::content_type;
 
-#[tracker::track]
 pub struct Item {
-    #[tracker::do_not_track]
-    index: DynamicIndex,
-    #[tracker::do_not_track]
+    entry: DirEntryInfo,
     name: String,
-    #[tracker::do_not_track]
-    path: String,
-    selected: bool,
-    #[tracker::do_not_track]
-    is_file: bool,
-    #[tracker::do_not_track]
-    icon_name: &'static str,
+    content_type: glib::GString,
+    icon: gio::Icon,
+    emblem: Option<&'static str>,
+    thumbnail: OnceCell<gdk::Texture>,
+    thumbnail_requested: Cell<bool>,
+    images: RefCell<Vec<glib::WeakRef<gtk::Image>>>,
+    collation_key: OnceCell<Box<[u8]>>,
+    natural_collation_key: OnceCell<Box<[u8]>>,
 }
 
-impl Item {
-    pub fn select(&mut self, selected: bool) {
-        self.set_selected(selected);
-    }
+fn icon() -> gtk::Overlay {
+    let image = gtk::Image::new();
+    image.set_css_classes(&["item-icon"]);
 
-    pub fn is_selected(&self) -> bool {
-        self.selected
-    }
+    let emblem = gtk::Image::new();
+    emblem.set_css_classes(&["item-emblem"]);
+    emblem.set_halign(gtk::Align::End);
+    emblem.set_valign(gtk::Align::End);
 
-    pub fn init(value: &PathBuf) -> ItemInit {
-        ItemInit::from(value)
+    let overlay = gtk::Overlay::new();
+    overlay.set_child(Some(&image));
+    overlay.add_overlay(&emblem);
+    overlay
+}
+
+/// A case-insensitive sort key for the current locale; the filename variant
+/// also orders digit runs by their value.
+fn collation_key(name: &str, natural: bool) -> Box<[u8]> {
+    let folded = glib::casefold(name);
+    // SAFETY: both functions read `len` bytes of valid UTF-8 and return a newly
+    // allocated nul-terminated string that is copied and then freed here.
+    unsafe {
+        let (text, len) = (folded.as_ptr(), folded.as_str().len() as isize);
+        let key = if natural {
+            glib::ffi::g_utf8_collate_key_for_filename(text, len)
+        } else {
+            glib::ffi::g_utf8_collate_key(text, len)
+        };
+        let bytes = CStr::from_ptr(key).to_bytes().into();
+        glib::ffi::g_free(key.cast());
+        bytes
     }
 }
 
-#[relm4::factory(pub)]
-impl FactoryComponent for Item {
-    type ParentWidget = gtk::Box;
-    type CommandOutput = ();
-    type Input = ItemInput;
-    type Output = ItemOutput;
-    type Init = ItemInit;
+fn item_path(object: &glib::Object) -> Option<std::path::PathBuf> {
+    let object = object.downcast_ref::<glib::BoxedAnyObject>()?;
+    let item = object.borrow::<Item>();
+    Some(item.entry().path().to_path_buf())
+}
 
-    fn init_model(init: Self::Init, index: &Self::Index, _: FactorySender<Self>) -> Self {
-        let icon_name = if init.is_file() { "file" } else { "folder" };
+impl Item {
+    pub fn new(entry: DirEntryInfo, content_type: glib::GString) -> Self {
         Self {
-            index: index.clone(),
-            name: init.name().to_owned(),
-            path: init.path().to_owned(),
-            selected: false,
-            is_file: init.is_file(),
-            icon_name,
-            tracker: 0,
+            name: entry.name().to_string_lossy().into_owned(),
+            icon: icon::icon(&entry, &content_type),
+            emblem: icon::emblem(&entry),
+            entry,
+            content_type,
+            thumbnail: OnceCell::new(),
+            thumbnail_requested: Cell::new(false),
+            images: RefCell::new(Vec::new()),
+            collation_key: OnceCell::new(),
+            natural_collation_key: OnceCell::new(),
         }
     }
 
-    fn update(&mut self, message: Self::Input, sender: FactorySender<Self>) {
-        self.reset();
-        match message {
-            Self::Input::ItemRightClicked => {
-                if self.selected {
-                    let output = if self.is_file {
-                        Self::Output::OpenFile(self.path.clone())
-                    } else {
-                        Self::Output::OpenDirectory(self.path.clone())
-                    };
-                    let _ = sender.output(output);
-                } else {
-                    let _ = sender.output(Self::Output::ItemSelected(self.index.clone()));
+    pub fn entry(&self) -> &DirEntryInfo {
+        &self.entry
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    pub fn collation_key(&self, natural: bool) -> &[u8] {
+        let key = if natural {
+            &self.natural_collation_key
+        } else {
+            &self.collation_key
+        };
+        key.get_or_init(|| collation_key(&self.name, natural))
+    }
+
+    pub fn setup(list_item: &gtk::ListItem) {
+        let row = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        row.set_css_classes(&["item-box"]);
+        row.append(&icon());
+        row.append(&gtk::Label::new(None));
+        Self::add_click_controllers(&row, list_item);
+
+        list_item.set_child(Some(&row));
+    }
+
+    pub fn setup_tile(list_item: &gtk::ListItem) {
+        let label = gtk::Label::new(None);
+        label.set_wrap(true);
+        label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
+        label.set_justify(gtk::Justification::Center);
+        label.set_lines(3);
+        label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        label.set_max_width_chars(14);
+
+        let tile = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        tile.set_css_classes(&["item-tile"]);
+        tile.append(&icon());
+        tile.append(&label);
+        Self::add_click_controllers(&tile, list_item);
+
+        list_item.set_child(Some(&tile));
+    }
+
+    /// A click on an already selected item opens it, a secondary click opens the context
+    /// menu on the item, selecting it unless it already is, a middle click opens a folder
+    /// in a new tab and a drag offers the item as a file.
+    pub fn add_click_controllers(widget: &impl IsA<gtk::Widget>, list_item: &gtk::ListItem) {
+        let was_selected = Rc::new(Cell::new(false));
+        let primary_click = gtk::GestureClick::new();
+        primary_click.set_button(gtk::gdk::ffi::GDK_BUTTON_PRIMARY as u32);
+        primary_click.connect_pressed({
+            let list_item = list_item.downgrade();
+            let was_selected = was_selected.clone();
+            move |_, n_press, _, _| {
+                let selected = list_item.upgrade().is_some_and(|item| item.is_selected());
+                was_selected.set(n_press == 1 && selected);
+            }
+        });
+        primary_click.connect_released({
+            let list_item = list_item.downgrade();
+            move |gesture, _, _, _| {
+                let Some(list_item) = list_item.upgrade() else {
+                    return;
+                };
+                if let Some(row) = gesture.widget().filter(|_| was_selected.get()) {
+                    let position = list_item.position().to_variant();
+                    let _ = row.activate_action("list.activate-item", Some(&position));
+                }
+            }
+        });
+        widget.add_controller(primary_click);
+
+        let secondary_click = gtk::GestureClick::new();
+        secondary_click.set_button(gtk::gdk::ffi::GDK_BUTTON_SECONDARY as u32);
+        secondary_click.connect_released({
+            let list_item = list_item.downgrade();
+            move |gesture, _, x, y| {
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+                let Some(row) = gesture.widget() else {
+                    return;
+                };
+                if !list_item.upgrade().is_some_and(|item| item.is_selected()) {
+                    let _ =
+                        row.activate_action("listitem.select", Some(&(false, false).to_variant()));
+                }
+                let point = gtk::graphene::Point::new(x as f32, y as f32);
+                let point = row
+                    .root()
+                    .and_then(|window| row.compute_point(&window, &point));
+                if let Some(point) = point {
+                    let position = (point.x() as f64, point.y() as f64).to_variant();
+                    let _ = row.activate_action("items.context_menu", Some(&position));
                 }
             }
-            Self::Input::ItemLeftClicked => {
-                if !self.selected {
-                    let _ = sender.output(Self::Output::ItemSelected(self.index.clone()));
+        });
+        widget.add_controller(secondary_click);
+
+        let middle_click = gtk::GestureClick::new();
+        middle_click.set_button(gtk::gdk::ffi::GDK_BUTTON_MIDDLE as u32);
+        middle_click.connect_released({
+            let list_item = list_item.downgrade();
+            move |gesture, _, _, _| {
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+                if let (Some(row), Some(list_item)) = (gesture.widget(), list_item.upgrade()) {
+                    let position = list_item.position().to_variant();
+                    let _ = row.activate_action("items.open_in_new_tab", Some(&position));
                 }
             }
+        });
+        widget.add_controller(middle_click);
+
+        let drag_source = gtk::DragSource::new();
+        drag_source.set_actions(gdk::DragAction::COPY | gdk::DragAction::LINK);
+        drag_source.connect_prepare({
+            let list_item = list_item.downgrade();
+            move |_, _, _| {
+                let object = list_item.upgrade()?.item()?;
+                let file = gio::File::for_path(item_path(&object)?);
+                let files = gdk::FileList::from_array(&[file]);
+                Some(gdk::ContentProvider::for_value(&files.to_value()))
+            }
+        });
+        widget.add_controller(drag_source);
+    }
+
+    pub fn bind(&self, list_item: &gtk::ListItem, thumbnails: &Thumbnails) {
+        let Some(row) = list_item.child() else {
+            return;
+        };
+        if let Some(icon) = row.first_child() {
+            if let Some(image) = icon.first_child().and_downcast::<gtk::Image>() {
+                self.show_icon(&image);
+                self.images.borrow_mut().push(image.downgrade());
+            }
+            if let Some(emblem) = icon.last_child().and_downcast::<gtk::Image>() {
+                emblem.set_icon_name(self.emblem);
+                emblem.set_visible(self.emblem.is_some());
+            }
+        }
+        if let Some(label) = row.last_child().and_downcast::<gtk::Label>() {
+            label.set_label(&self.name);
+        }
+
+        if !self.thumbnail_requested.replace(true) {
+            thumbnails.request(&self.entry, &self.content_type);
         }
     }
 
-    view! {
-        gtk::Box {
-            set_orientation: gtk::Orientation::Horizontal,
-            #[track = "self.changed(Item::selected())"]
-            set_class_active: ("selected", self.selected),
-            set_css_classes: &["item-box"],
-            add_controller = gtk::GestureClick {
-                set_button: gtk::gdk::ffi::GDK_BUTTON_PRIMARY as u32,
-                connect_released[sender] => move |gesture, _, _, _| {
-                    gesture.set_state(gtk::EventSequenceState::Claimed);
-                    sender.input(Self::Input::ItemRightClicked);
-                }
-            },
-            add_controller = gtk::GestureClick {
-                set_button: gtk::gdk::ffi::GDK_BUTTON_SECONDARY as u32,
-                connect_released[sender] => move |gesture, _, _, _| {
-                    gesture.set_state(gtk::EventSequenceState::Claimed);
-                    sender.input(Self::Input::ItemLeftClicked);
-                }
-            },
-            gtk::Image {
-                set_icon_name: Some(self.icon_name),
-            },
-            gtk::Label {
-                set_label: &self.name,
+    pub fn unbind(&self, list_item: &gtk::ListItem) {
+        let image = list_item
+            .child()
+            .and_then(|row| row.first_child())
+            .and_then(|icon| icon.first_child());
+        self.images.borrow_mut().retain(|bound| {
+            bound
+                .upgrade()
+                .is_some_and(|bound| image.as_ref() != Some(bound.upcast_ref()))
+        });
+    }
+
+    /// Replaces the icon with the thumbnail, also in the rows showing the item right now.
+    pub fn set_thumbnail(&self, thumbnail: gdk::Texture) {
+        if self.thumbnail.set(thumbnail).is_ok() {
+            for image in self
+                .images
+                .borrow()
+                .iter()
+                .filter_map(|image| image.upgrade())
+            {
+                self.show_icon(&image);
             }
         }
     }
+
+    /// The label showing the name in the view on screen, if the item is bound there.
+    pub fn bound_label(&self) -> Option<gtk::Label> {
+        self.images
+            .borrow()
+            .iter()
+            .filter_map(|image| image.upgrade())
+            .filter(|image| image.is_mapped())
+            .find_map(|image| {
+                let row = image.parent()?.parent()?;
+                row.last_child().and_downcast::<gtk::Label>()
+            })
+    }
+
+    fn show_icon(&self, image: &gtk::Image) {
+        match self.thumbnail.get() {
+            Some(thumbnail) => image.set_paintable(Some(thumbnail)),
+            None => image.set_from_gicon(&self.icon),
+        }
+    }
 }
@@ -0,0 +1,366 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use relm4::gtk::{self, gdk, gio, glib, prelude::*};
+
+use super::column::Column;
+use super::item::Item;
+use super::sorting::{SortKey, Sorting};
+use super::thumbnails::Thumbnails;
+use crate::core::DirEntryInfo;
+
+pub struct ItemsList {
+    store: gio::ListStore,
+    /// The position of each path in `store`.
+    positions: RefCell<HashMap<PathBuf, u32>>,
+    filter: gtk::CustomFilter,
+    sorter: gtk::CustomSorter,
+    selection: gtk::MultiSelection,
+    list_view: gtk::ListView,
+    grid_view: gtk::GridView,
+    column_view: gtk::ColumnView,
+    show_hidden_items: Rc<Cell<bool>>,
+    sorting: Rc<Cell<Sorting>>,
+    thumbnails: Rc<Thumbnails>,
+}
+
+fn item(object: &glib::Object) -> std::cell::Ref<'_, Item> {
+    object
+        .downcast_ref::<glib::BoxedAnyObject>()
+        .expect("Items list contains only items")
+        .borrow()
+}
+
+fn list_item(object: &glib::Object) -> &gtk::ListItem {
+    object
+        .downcast_ref::<gtk::ListItem>()
+        .expect("Needs to be ListItem")
+}
+
+fn item_factory(thumbnails: &Rc<Thumbnails>) -> gtk::SignalListItemFactory {
+    let factory = gtk::SignalListItemFactory::new();
+    factory.connect_setup(|_, object| Item::setup(list_item(object)));
+    connect_bind(&factory, thumbnails);
+    factory
+}
+
+fn tile_factory(thumbnails: &Rc<Thumbnails>) -> gtk::SignalListItemFactory {
+    let factory = gtk::SignalListItemFactory::new();
+    factory.connect_setup(|_, object| Item::setup_tile(list_item(object)));
+    connect_bind(&factory, thumbnails);
+    factory
+}
+
+fn connect_bind(factory: &gtk::SignalListItemFactory, thumbnails: &Rc<Thumbnails>) {
+    let thumbnails = thumbnails.clone();
+    factory.connect_bind(move |_, object| {
+        let list_item = list_item(object);
+        if let Some(object) = list_item.item() {
+            item(&object).bind(list_item, &thumbnails);
+        }
+    });
+    factory.connect_unbind(|_, object| {
+        let list_item = list_item(object);
+        if let Some(object) = list_item.item() {
+            item(&object).unbind(list_item);
+        }
+    });
+}
+
+fn column_factory(column: Column, thumbnails: &Rc<Thumbnails>) -> gtk::SignalListItemFactory {
+    if column == Column::Name {
+        return item_factory(thumbnails);
+    }
+
+    let factory = gtk::SignalListItemFactory::new();
+    factory.connect_setup(|_, object| {
+        let list_item = list_item(object);
+        let label = gtk::Label::new(None);
+        label.set_xalign(0.0);
+        label.set_css_classes(&["item-column"]);
+        Item::add_click_controllers(&label, list_item);
+        list_item.set_child(Some(&label));
+    });
+    factory.connect_bind(move |_, object| {
+        let list_item = list_item(object);
+        let label = list_item.child().and_downcast::<gtk::Label>();
+        if let (Some(label), Some(object)) = (label, list_item.item()) {
+            label.set_label(&column.text(&item(&object)));
+        }
+    });
+    factory
+}
+
+impl ItemsList {
+    pub fn new(
+        show_hidden_items: bool,
+        on_thumbnail: impl Fn(PathBuf, gdk::Texture) + Send + Sync + 'static,
+    ) -> Self {
+        let thumbnails = Rc::new(Thumbnails::new(on_thumbnail));
+        let store = gio::ListStore::new::<glib::BoxedAnyObject>();
+        let show_hidden_items = Rc::new(Cell::new(show_hidden_items));
+        let sorting = Rc::new(Cell::new(Sorting::default()));
+
+        let filter = gtk::CustomFilter::new({
+            let show_hidden_items = show_hidden_items.clone();
+            move |object| show_hidden_items.get() || !item(object).entry().is_hidden()
+        });
+        let sorter = gtk::CustomSorter::new({
+            let sorting = sorting.clone();
+            move |first, second| sorting.get().compare(&item(first), &item(second)).into()
+        });
+
+        let filter_model = gtk::FilterListModel::new(Some(store.clone()), Some(filter.clone()));
+        let sort_model = gtk::SortListModel::new(Some(filter_model), Some(sorter.clone()));
+        let selection = gtk::MultiSelection::new(Some(sort_model));
+
+        let list_view =
+            gtk::ListView::new(Some(selection.clone()), Some(item_factory(&thumbnails)));
+        list_view.set_css_classes(&["items-list"]);
+
+        let grid_view =
+            gtk::GridView::new(Some(selection.clone()), Some(tile_factory(&thumbnails)));
+        grid_view.set_css_classes(&["items-grid"]);
+        grid_view.set_max_columns(64);
+
+        let column_view = gtk::ColumnView::new(Some(selection.clone()));
+        column_view.set_css_classes(&["items-details"]);
+        column_view.set_show_column_separators(true);
+        for column in Column::ALL {
+            let view_column = gtk::ColumnViewColumn::new(
+                Some(column.title()),
+                Some(column_factory(column, &thumbnails)),
+            );
+            view_column.set_id(Some(column.id()));
+            view_column.set_resizable(true);
+            view_column.set_expand(column == Column::Name);
+            // The sorters only make the headers clickable, the rows are ordered by `sorting`.
+            view_column.set_sorter(Some(&gtk::CustomSorter::new(|_, _| gtk::Ordering::Equal)));
+            column_view.append_column(&view_column);
+        }
+        column_view.sort_by_column(
+            column_view
+                .columns()
+                .item(0)
+                .and_downcast::<gtk::ColumnViewColumn>()
+                .as_ref(),
+            gtk::SortType::Ascending,
+        );
+
+        Self {
+            store,
+            positions: RefCell::default(),
+            filter,
+            sorter,
+            selection,
+            list_view,
+            grid_view,
+            column_view,
+            show_hidden_items,
+            sorting,
+            thumbnails,
+        }
+    }
+
+    pub fn list_view(&self) -> &gtk::ListView {
+        &self.list_view
+    }
+
+    pub fn grid_view(&self) -> &gtk::GridView {
+        &self.grid_view
+    }
+
+    pub fn column_view(&self) -> &gtk::ColumnView {
+        &self.column_view
+    }
+
+    pub fn clear(&self) {
+        self.thumbnails.cancel_pending();
+        self.store.remove_all();
+        self.positions.borrow_mut().clear();
+    }
+
+    pub fn extend(&self, items: impl IntoIterator<Item = Item>) {
+        let objects: Vec<glib::BoxedAnyObject> =
+            items.into_iter().map(glib::BoxedAnyObject::new).collect();
+        self.append(&objects);
+    }
+
+    fn append(&self, objects: &[glib::BoxedAnyObject]) {
+        let start = self.store.n_items();
+        self.positions
+            .borrow_mut()
+            .extend(objects.iter().zip(start..).map(|(object, position)| {
+                let path = object.borrow::<Item>().entry().path().to_path_buf();
+                (path, position)
+            }));
+        self.store.extend_from_slice(objects);
+    }
+
+    /// The item at `path`, found through `positions`.
+    fn object(&self, path: &Path) -> Option<glib::Object> {
+        let position = *self.positions.borrow().get(path)?;
+        self.store.item(position)
+    }
+
+    /// Applies re-read entries to the list in place, so the scroll position and the
+    /// selection survive; `None` removes the entry.
+    pub fn update(&self, changes: Vec<(PathBuf, Option<Item>)>) {
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        for (path, changed) in changes {
+            let position = self.positions.borrow().get(&path).copied();
+            match (position, changed) {
+                (Some(position), Some(changed)) => {
+                    let object = self
+                        .store
+                        .item(position)
+                        .and_downcast::<glib::BoxedAnyObject>();
+                    if let Some(object) = object {
+                        object.replace(changed);
+                        self.store.items_changed(position, 1, 1);
+                    }
+                }
+                (Some(position), None) => removed.push(position),
+                (None, Some(changed)) => added.push(glib::BoxedAnyObject::new(changed)),
+                (None, None) => {}
+            }
+        }
+
+        if !removed.is_empty() {
+            removed.sort_unstable_by(|a, b| b.cmp(a));
+            for position in removed {
+                self.store.remove(position);
+            }
+            // The items after the removed ones moved up.
+            let positions = (0..self.store.n_items()).filter_map(|position| {
+                let object = self.store.item(position)?;
+                let path = item(&object).entry().path().to_path_buf();
+                Some((path, position))
+            });
+            *self.positions.borrow_mut() = positions.collect();
+        }
+        self.append(&added);
+    }
+
+    pub fn set_show_hidden_items(&self, show: bool) {
+        if self.show_hidden_items.replace(show) != show {
+            let change = if show {
+                gtk::FilterChange::LessStrict
+            } else {
+                gtk::FilterChange::MoreStrict
+            };
+            self.filter.changed(change);
+        }
+    }
+
+    pub fn set_column_visible(&self, column: Column, visible: bool) {
+        if let Some(view_column) = self.view_column(column) {
+            view_column.set_visible(visible || column == Column::Name);
+        }
+    }
+
+    pub fn set_sorting(&self, sorting: Sorting) {
+        if self.sorting.replace(sorting) == sorting {
+            return;
+        }
+
+        self.sorter.changed(gtk::SorterChange::Different);
+        let order = if sorting.descending {
+            gtk::SortType::Descending
+        } else {
+            gtk::SortType::Ascending
+        };
+        let view_column = sorting
+            .key
+            .column()
+            .and_then(|column| self.view_column(column));
+        self.column_view.sort_by_column(view_column.as_ref(), order);
+    }
+
+    /// Reports the sorting requested by clicking the column headers of the details view.
+    pub fn connect_sorting_changed<F: Fn(SortKey, bool) + 'static>(&self, f: F) {
+        let Some(sorter) = self
+            .column_view
+            .sorter()
+            .and_downcast::<gtk::ColumnViewSorter>()
+        else {
+            return;
+        };
+
+        sorter.connect_changed(move |sorter, _| {
+            let column = sorter
+                .primary_sort_column()
+                .and_then(|column| column.id())
+                .and_then(|id| Column::from_id(&id));
+            if let Some(column) = column {
+                let descending = sorter.primary_sort_order() == gtk::SortType::Descending;
+                f(column.into(), descending);
+            }
+        });
+    }
+
+    pub fn set_thumbnail(&self, path: &Path, thumbnail: gdk::Texture) {
+        if let Some(object) = self.object(path) {
+            item(&object).set_thumbnail(thumbnail);
+        }
+    }
+
+    /// The label of the item at `path` in the visible view.
+    pub fn label(&self, path: &Path) -> Option<gtk::Label> {
+        self.object(path)
+            .and_then(|object| item(&object).bound_label())
+    }
+
+    pub fn get(&self, position: u32) -> Option<DirEntryInfo> {
+        self.selection
+            .item(position)
+            .map(|object| item(&object).entry().clone())
+    }
+
+    /// The entries of the selected items in view order.
+    pub fn selected(&self) -> Vec<DirEntryInfo> {
+        let selection = self.selection.selection();
+        (0..selection.size() as u32)
+            .filter_map(|index| self.get(selection.nth(index)))
+            .collect()
+    }
+
+    /// Selects the item at `path` alone and scrolls the views to it.
+    pub fn select_path(&self, path: &Path) {
+        let position = (0..self.selection.n_items()).find(|position| {
+            self.selection
+                .item(*position)
+                .is_some_and(|object| item(&object).entry().path() == path)
+        });
+        let Some(position) = position else {
+            return;
+        };
+
+        self.selection.select_item(position, true);
+        let column_list =
+            std::iter::successors(self.column_view.first_child(), |child| child.next_sibling())
+                .find(|child| child.is::<gtk::ListView>());
+        let views = [
+            Some(self.list_view.clone().upcast::<gtk::Widget>()),
+            Some(self.grid_view.clone().upcast()),
+            column_list,
+        ];
+        for view in views.into_iter().flatten() {
+            let _ = view.activate_action("list.scroll-to-item", Some(&position.to_variant()));
+        }
+    }
+
+    fn view_column(&self, column: Column) -> Option<gtk::ColumnViewColumn> {
+        let columns = self.column_view.columns();
+        (0..columns.n_items())
+            .filter_map(|position| {
+                columns
+                    .item(position)
+                    .and_downcast::<gtk::ColumnViewColumn>()
+            })
+            .find(|view_column| view_column.id().as_deref() == Some(column.id()))
+    }
+}
@@ -1,89 +1,284 @@
+mod clipboard;
+mod column;
+mod command;
 mod init;
 mod input;
 mod item;
+mod items_list;
+mod navigation;
 mod output;
+mod sorting;
+mod thumbnails;
+mod view_mode;
+mod watcher;
+mod zoom;
 
-use std::path::PathBuf;
-
+pub(crate) use column::Column;
+use command::ItemsBoxCommand;
 use init::ItemsBoxInit;
 pub(crate) use input::ItemsBoxInput;
 pub(crate) use output::ItemsBoxOutput;
-use relm4::gtk::prelude::*;
-use relm4::{factory::FactoryVecDeque, prelude::*};
+use relm4::gtk::{gdk, gio, glib, graphene, prelude::*};
+use relm4::{JoinHandle, prelude::*};
+pub(crate) use sorting::{SortKey, SortOption, Sorting};
+pub(crate) use view_mode::ViewMode;
+use watcher::DirectoryWatcher;
+pub(crate) use zoom::Zoom;
+
+use item::Item;
+use items_list::ItemsList;
+use navigation::Navigation;
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use crate::core::{
+    DirEntryInfo, History, ItemsReader, Operation, TransferMode, extension, open_item, templates,
+    tree_size,
+};
+use crate::menubar::{new_entry_menu, selection_menu};
+use crate::shortcuts::items_shortcuts;
+use crate::ui::batch_rename::{BatchRename, BatchRenameOutput};
+use crate::ui::dialogs::confirm;
 
-use item::{Item, ItemOutput};
+const LOADING_CHUNK_SIZE: usize = 500;
 
-use crate::core::{load_items, open_item};
+fn context_menu(parent: &gtk::Overlay) -> gtk::PopoverMenu {
+    let menu = gio::Menu::new();
+    menu.append_section(None, &new_entry_menu(&[]));
+    menu.append_section(None, &selection_menu());
 
-trait Hidden {
-    fn is_hidden(&self) -> bool;
+    let popover = gtk::PopoverMenu::from_model(Some(&menu));
+    popover.set_parent(parent);
+    popover.set_has_arrow(false);
+    popover.set_halign(gtk::Align::Start);
+    parent.connect_destroy({
+        let popover = popover.clone();
+        move |_| popover.unparent()
+    });
+    popover
+}
+
+fn with_content_type(entry: DirEntryInfo) -> (DirEntryInfo, glib::GString) {
+    let content_type = item::content_type(&entry);
+    (entry, content_type)
 }
 
 #[tracker::track]
 pub struct ItemsBox {
     #[tracker::do_not_track]
-    items: FactoryVecDeque<Item>,
+    items: ItemsList,
     show_hidden_items: bool,
     current_dir: String,
+    loading: bool,
+    view_mode: ViewMode,
+    zoom: Zoom,
+    #[tracker::do_not_track]
+    sorting: Sorting,
+    #[tracker::do_not_track]
+    load_id: usize,
+    #[tracker::do_not_track]
+    loading_task: Option<JoinHandle<()>>,
+    #[tracker::do_not_track]
+    history: History,
+    #[tracker::do_not_track]
+    navigation: Navigation,
+    #[tracker::do_not_track]
+    watcher: Option<DirectoryWatcher>,
+    #[tracker::do_not_track]
+    deferred_changes: Vec<PathBuf>,
+    #[tracker::do_not_track]
+    pending_selection: Option<PathBuf>,
+    #[tracker::do_not_track]
+    batch_rename: Option<Controller<BatchRename>>,
+    /// The entry being created, renamed as soon as it shows up.
+    #[tracker::do_not_track]
+    pending_rename: Option<PathBuf>,
+    #[tracker::do_not_track]
+    context_menu: gtk::PopoverMenu,
 }
 
 impl ItemsBox {
-    fn update_items(&mut self, items: &[PathBuf]) {
-        self.items.guard().clear();
-        for (index, path_buf) in items
-            .iter()
-            .filter(|p| !p.is_hidden() || (self.show_hidden_items && p.is_hidden()))
-            .enumerate()
-        {
-            self.items.guard().insert(index, Item::init(path_buf));
-        }
+    fn append_items(&self, items: Vec<(DirEntryInfo, glib::GString)>) {
+        self.items.extend(
+            items
+                .into_iter()
+                .map(|(entry, content_type)| Item::new(entry, content_type)),
+        );
     }
 
-    fn sort_by_type(a: &PathBuf, b: &PathBuf) -> std::cmp::Ordering {
-        if a.is_dir() && b.is_file() {
-            std::cmp::Ordering::Less
-        } else if a.is_file() && b.is_dir() {
-            std::cmp::Ordering::Greater
-        } else {
-            a.file_name().cmp(&b.file_name())
+    /// Re-reads the changed entries off the main thread; changes arriving while the
+    /// directory is still loading wait until it is done, so nothing is added twice.
+    fn refresh_items(&mut self, paths: Vec<PathBuf>, sender: &ComponentSender<Self>) {
+        let current_dir = Path::new(&self.current_dir);
+        let paths = paths
+            .into_iter()
+            .filter(|path| path.parent() == Some(current_dir));
+        if self.loading {
+            self.deferred_changes.extend(paths);
+            return;
+        }
+
+        let paths: Vec<PathBuf> = paths.collect();
+        if paths.is_empty() {
+            return;
         }
+        let load_id = self.load_id;
+        sender.spawn_oneshot_command(move || {
+            let changes = paths
+                .into_iter()
+                .map(|path| {
+                    let item = DirEntryInfo::read(&path).ok().map(with_content_type);
+                    (path, item)
+                })
+                .collect();
+            ItemsBoxCommand::ItemsChanged(load_id, changes)
+        });
     }
 
-    pub fn init(current_dir: &str, show_hidden_items: bool) -> ItemsBoxInit {
-        ItemsBoxInit::new(current_dir.to_owned(), show_hidden_items)
+    fn update_history(&mut self, current_dir: String, sender: &ComponentSender<Self>) {
+        match self.navigation {
+            Navigation::Visit => self.history.visit(current_dir),
+            Navigation::Back(steps) => {
+                self.history.go_back(steps);
+            }
+            Navigation::Forward(steps) => {
+                self.history.go_forward(steps);
+            }
+        }
+
+        let back = self.history.back_entries().map(str::to_owned).collect();
+        let forward = self.history.forward_entries().map(str::to_owned).collect();
+        let _ = sender.output(ItemsBoxOutput::HistoryChanged(back, forward));
     }
-}
 
-impl Hidden for PathBuf {
-    #[cfg(unix)]
-    fn is_hidden(&self) -> bool {
-        self.file_name()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .as_bytes()
-            .starts_with(b".")
+    fn sort(&mut self, sorting: Sorting, sender: &ComponentSender<Self>) {
+        if self.sorting != sorting {
+            self.sorting = sorting;
+            self.items.set_sorting(sorting);
+            let _ = sender.output(ItemsBoxOutput::SortingChanged(sorting));
+        }
     }
 
-    #[cfg(windows)]
-    fn is_hidden(&self) -> bool {
-        use std::os::windows::prelude::*;
-        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x00000002;
+    fn load_directory(
+        &mut self,
+        current_dir: String,
+        navigation: Navigation,
+        sender: &ComponentSender<Self>,
+    ) {
+        if let Some(task) = self.loading_task.take() {
+            task.abort();
+        }
+
+        self.navigation = navigation;
+        self.load_id += 1;
+        self.set_loading(true);
 
-        match self.metadata() {
-            Ok(metadata) => {
-                let attributes = metadata.file_attributes();
-                if attributes & FILE_ATTRIBUTE_HIDDEN != 0 {
-                    return true;
+        let load_id = self.load_id;
+        let command_sender = sender.command_sender().clone();
+        self.loading_task = Some(relm4::spawn(async move {
+            let mut reader = match ItemsReader::open(&current_dir, LOADING_CHUNK_SIZE).await {
+                Ok(reader) => reader,
+                Err(error) => {
+                    command_sender.emit(ItemsBoxCommand::LoadingFailed(load_id, error.to_string()));
+                    return;
                 }
+            };
 
-                false
-            }
-            Err(err) => {
-                println!("Error occured: [{:?}]", err.kind());
-                false
+            command_sender.emit(ItemsBoxCommand::DirectoryOpened(load_id, current_dir));
+            loop {
+                let entries = match reader.next_chunk().await {
+                    Ok(Some(entries)) => entries,
+                    Ok(None) => break,
+                    Err(error) => {
+                        let error = format!("{error:#}, the listing is incomplete");
+                        command_sender.emit(ItemsBoxCommand::LoadingFailed(load_id, error));
+                        return;
+                    }
+                };
+                let items = tokio::task::spawn_blocking(move || {
+                    entries.into_iter().map(with_content_type).collect()
+                })
+                .await
+                .unwrap_or_default();
+                command_sender.emit(ItemsBoxCommand::ItemsLoaded(load_id, items));
             }
+            command_sender.emit(ItemsBoxCommand::LoadingFinished(load_id));
+        }));
+    }
+
+    fn selected_paths(&self) -> Vec<PathBuf> {
+        self.items
+            .selected()
+            .iter()
+            .map(|entry| entry.path().to_path_buf())
+            .collect()
+    }
+
+    /// Asks for the new name of an item in a popover on its label, with the name
+    /// selected up to the extension.
+    fn start_rename(
+        &self,
+        entry: &DirEntryInfo,
+        root: &gtk::Overlay,
+        sender: &ComponentSender<Self>,
+    ) {
+        let name = entry.name().to_string_lossy();
+        let stem = match entry.is_dir() {
+            true => name.as_ref(),
+            false => match extension(&name) {
+                "" => name.as_ref(),
+                extension => &name[..name.len() - extension.len() - 1],
+            },
+        };
+
+        let name_entry = gtk::Entry::new();
+        name_entry.set_text(&name);
+        name_entry.set_width_chars(name.chars().count().clamp(12, 40) as i32);
+        // Parented to the window, so that the keys typed into it skip the items box shortcuts.
+        let Some(window) = root.root().and_upcast::<gtk::Widget>() else {
+            return;
+        };
+        let target = match self.items.label(entry.path()) {
+            Some(label) => label.upcast(),
+            None => root.clone().upcast::<gtk::Widget>(),
+        };
+        let popover = gtk::Popover::new();
+        popover.set_child(Some(&name_entry));
+        popover.set_parent(&window);
+        if let Some(bounds) = target.compute_bounds(&window) {
+            popover.set_pointing_to(Some(&gdk::Rectangle::new(
+                bounds.x() as i32,
+                bounds.y() as i32,
+                bounds.width() as i32,
+                bounds.height() as i32,
+            )));
         }
+        popover.connect_closed(|popover| popover.unparent());
+        name_entry.connect_activate({
+            let sender = sender.clone();
+            let path = entry.path().to_path_buf();
+            let old_name = name.to_string();
+            let popover = popover.downgrade();
+            move |name_entry| {
+                // Compared with the text it was filled with, as a name that is not UTF-8 never
+                // equals its lossy text.
+                let name = name_entry.text().to_string();
+                if name != old_name {
+                    sender.input(ItemsBoxInput::Rename(vec![path.clone()], vec![name]));
+                }
+                if let Some(popover) = popover.upgrade() {
+                    popover.popdown();
+                }
+            }
+        });
+        popover.popup();
+        name_entry.grab_focus();
+        name_entry.select_region(0, stem.chars().count() as i32);
+    }
+
+    pub fn init(current_dir: &str, show_hidden_items: bool) -> ItemsBoxInit {
+        ItemsBoxInit::new(current_dir.to_owned(), show_hidden_items)
     }
 }
 
@@ -92,7 +287,7 @@ impl Component for ItemsBox {
     type Init = ItemsBoxInit;
     type Input = ItemsBoxInput;
     type Output = ItemsBoxOutput;
-    type CommandOutput = ();
+    type CommandOutput = ItemsBoxCommand;
 
     fn init(
         init: Self::Init,
@@ -102,25 +297,80 @@ impl Component for ItemsBox {
         let mut item_box = Self {
             current_dir: init.current_dir().to_owned(),
             show_hidden_items: init.show_hidden_items(),
-            items: FactoryVecDeque::builder()
-                .launch(gtk::Box::new(gtk::Orientation::Vertical, 0))
-                .forward(sender.input_sender(), convert_item_response),
+            items: ItemsList::new(init.show_hidden_items(), {
+                let command_sender = sender.command_sender().clone();
+                move |path, thumbnail| {
+                    command_sender.emit(ItemsBoxCommand::ThumbnailLoaded(path, thumbnail));
+                }
+            }),
+            loading: false,
+            view_mode: ViewMode::List,
+            zoom: Zoom::default(),
+            sorting: Sorting::default(),
+            load_id: 0,
+            loading_task: None,
+            history: History::default(),
+            navigation: Navigation::Visit,
+            watcher: None,
+            deferred_changes: Vec::new(),
+            pending_selection: None,
+            batch_rename: None,
+            pending_rename: None,
+            context_menu: context_menu(&root),
             tracker: 0,
         };
 
-        match load_items(init.current_dir()) {
-            Err(error) => {
-                let _ = sender.output(Self::Output::Error(error.to_string()));
-            }
-            Ok(mut items) => {
-                items.sort_by(Self::sort_by_type);
-                item_box.update_items(&items);
-                let _ = sender.output(Self::Output::DirectoryLoaded(init.current_dir().to_owned()));
-            }
-        }
+        item_box.load_directory(init.current_dir().to_owned(), Navigation::Visit, &sender);
+        item_box.items.connect_sorting_changed({
+            let sender = sender.clone();
+            move |key, descending| sender.input(ItemsBoxInput::Sort(key, descending))
+        });
 
+        let list_view = item_box.items.list_view();
+        let grid_view = item_box.items.grid_view();
+        let column_view = item_box.items.column_view();
         let widgets = view_output!();
 
+        let actions = gio::SimpleActionGroup::new();
+        actions.add_action_entries([
+            gio::ActionEntry::builder("open_in_new_tab")
+                .parameter_type(Some(glib::VariantTy::UINT32))
+                .activate({
+                    let sender = sender.clone();
+                    move |_: &gio::SimpleActionGroup, _, parameter: Option<&glib::Variant>| {
+                        if let Some(position) = parameter.and_then(glib::Variant::get::<u32>) {
+                            sender.input(ItemsBoxInput::OpenInNewTab(position));
+                        }
+                    }
+                })
+                .build(),
+            // Items pass the point in window coordinates, since they do not know the box.
+            gio::ActionEntry::builder("context_menu")
+                .parameter_type(Some(&<(f64, f64)>::static_variant_type()))
+                .activate({
+                    let sender = sender.clone();
+                    let root = root.downgrade();
+                    move |_: &gio::SimpleActionGroup, _, parameter: Option<&glib::Variant>| {
+                        let (Some((x, y)), Some(root)) = (
+                            parameter.and_then(glib::Variant::get::<(f64, f64)>),
+                            root.upgrade(),
+                        ) else {
+                            return;
+                        };
+                        let point = graphene::Point::new(x as f32, y as f32);
+                        let point = root
+                            .root()
+                            .and_then(|window| window.compute_point(&root, &point));
+                        if let Some(point) = point {
+                            let (x, y) = (point.x() as f64, point.y() as f64);
+                            sender.input(ItemsBoxInput::ShowContextMenu(x, y));
+                        }
+                    }
+                })
+                .build(),
+        ]);
+        root.insert_action_group("items", Some(&actions));
+
         ComponentParts {
             model: item_box,
             widgets,
@@ -128,61 +378,366 @@ impl Component for ItemsBox {
     }
 
     view! {
-        gtk::ScrolledWindow {
-            set_hscrollbar_policy: gtk::PolicyType::Automatic,
+        gtk::Overlay {
             set_vexpand: true,
-            item_box.items.widget(),
+            add_controller: items_shortcuts(),
+            add_controller = gtk::GestureClick {
+                set_button: gdk::BUTTON_SECONDARY,
+                connect_released[sender] => move |gesture, _, x, y| {
+                    gesture.set_state(gtk::EventSequenceState::Claimed);
+                    sender.input(ItemsBoxInput::ShowContextMenu(x, y));
+                },
+            },
+            add_controller = gtk::EventControllerScroll::new(
+                gtk::EventControllerScrollFlags::VERTICAL
+                    | gtk::EventControllerScrollFlags::DISCRETE,
+            ) {
+                set_propagation_phase: gtk::PropagationPhase::Capture,
+                connect_scroll[sender] => move |controller, _, dy| {
+                    let zoom = controller
+                        .current_event_state()
+                        .contains(gtk::gdk::ModifierType::CONTROL_MASK);
+                    if !zoom || dy == 0.0 {
+                        return glib::Propagation::Proceed;
+                    }
+                    let _ = sender.output(if dy < 0.0 {
+                        ItemsBoxOutput::ZoomIn
+                    } else {
+                        ItemsBoxOutput::ZoomOut
+                    });
+                    glib::Propagation::Stop
+                },
+            },
+
+            gtk::Stack {
+                #[track = "item_box.changed(ItemsBox::zoom())"]
+                set_css_classes: &[item_box.zoom.css_class()],
+
+                add_named[Some(ViewMode::List.id())] = &gtk::ScrolledWindow {
+                    set_hscrollbar_policy: gtk::PolicyType::Automatic,
+                    set_vexpand: true,
+                    #[local_ref]
+                    list_view -> gtk::ListView {
+                        connect_activate[sender] => move |_, position| {
+                            sender.input(ItemsBoxInput::ActivateItem(position));
+                        },
+                    },
+                },
+                add_named[Some(ViewMode::Grid.id())] = &gtk::ScrolledWindow {
+                    set_hscrollbar_policy: gtk::PolicyType::Never,
+                    set_vexpand: true,
+                    #[local_ref]
+                    grid_view -> gtk::GridView {
+                        connect_activate[sender] => move |_, position| {
+                            sender.input(ItemsBoxInput::ActivateItem(position));
+                        },
+                    },
+                },
+                add_named[Some(ViewMode::Details.id())] = &gtk::ScrolledWindow {
+                    set_hscrollbar_policy: gtk::PolicyType::Automatic,
+                    set_vexpand: true,
+                    #[local_ref]
+                    column_view -> gtk::ColumnView {
+                        connect_activate[sender] => move |_, position| {
+                            sender.input(ItemsBoxInput::ActivateItem(position));
+                        },
+                    },
+                },
+                #[track = "item_box.changed(ItemsBox::view_mode())"]
+                set_visible_child_name: item_box.view_mode.id(),
+            },
+
+            add_overlay = &gtk::Spinner {
+                set_halign: gtk::Align::Center,
+                set_valign: gtk::Align::Center,
+                set_width_request: 32,
+                set_height_request: 32,
+                #[track = "item_box.changed(ItemsBox::loading())"]
+                set_spinning: item_box.loading,
+                #[track = "item_box.changed(ItemsBox::loading())"]
+                set_visible: item_box.loading,
+            },
         }
     }
 
-    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _: &Self::Root) {
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
         self.reset();
         match message {
-            Self::Input::LoadDirectory(current_dir) => match load_items(&current_dir) {
-                Err(error) => {
-                    let _ = sender.output(Self::Output::Error(error.to_string()));
+            Self::Input::LoadDirectory(current_dir) => {
+                self.load_directory(current_dir, Navigation::Visit, &sender);
+            }
+            Self::Input::Back(steps) => {
+                if let Some(location) = self.history.back_location(steps) {
+                    let location = location.to_owned();
+                    self.load_directory(location, Navigation::Back(steps), &sender);
                 }
-                Ok(mut items) => {
-                    self.set_current_dir(current_dir.to_owned());
-                    items.sort_by(Self::sort_by_type);
-                    self.update_items(&items);
-                    let _ = sender.output(Self::Output::DirectoryLoaded(current_dir.to_owned()));
+            }
+            Self::Input::Forward(steps) => {
+                if let Some(location) = self.history.forward_location(steps) {
+                    let location = location.to_owned();
+                    self.load_directory(location, Navigation::Forward(steps), &sender);
                 }
-            },
-            Self::Input::OpenFile(path) => {
-                if let Err(error) = open_item(&path) {
+            }
+            Self::Input::Up => {
+                let parent = Path::new(&self.current_dir)
+                    .parent()
+                    .map(|parent| parent.display().to_string());
+                if let Some(parent) = parent {
+                    self.load_directory(parent, Navigation::Visit, &sender);
+                }
+            }
+            Self::Input::ActivateItem(position) => {
+                if let Some(entry) = self.items.get(position) {
+                    let path = entry.path().display().to_string();
+                    if entry.is_dir() {
+                        self.load_directory(path, Navigation::Visit, &sender);
+                    } else if let Err(error) = open_item(&path) {
+                        let _ = sender.output(Self::Output::Error(error.to_string()));
+                    }
+                }
+            }
+            Self::Input::OpenInNewTab(position) => {
+                if let Some(entry) = self.items.get(position).filter(DirEntryInfo::is_dir) {
+                    let path = entry.path().display().to_string();
+                    let _ = sender.output(Self::Output::OpenInNewTab(path));
+                }
+            }
+            Self::Input::ItemsChanged(paths) => self.refresh_items(paths, &sender),
+            Self::Input::Select(path) => {
+                if self.loading {
+                    self.pending_selection = Some(path);
+                } else {
+                    self.items.select_path(&path);
+                }
+            }
+            Self::Input::TransferSelection(mode, dest_dir) => {
+                let sources = self.selected_paths();
+                if !sources.is_empty() {
+                    let operation = Operation::Transfer(mode, sources, dest_dir.into());
+                    let _ = sender.output(Self::Output::StartJob(operation));
+                }
+            }
+            Self::Input::CopyToClipboard(mode) => {
+                let paths = self.selected_paths();
+                if paths.is_empty() {
+                    return;
+                }
+                if let Err(error) = clipboard::set_files(&root.clipboard(), mode, &paths) {
                     let _ = sender.output(Self::Output::Error(error.to_string()));
                 }
             }
-            Self::Input::SelectItem(index) => {
-                self.items
-                    .guard()
-                    .iter_mut()
-                    .filter(|item| item.is_selected())
-                    .for_each(|item| item.select(false));
-
-                if let Some(selected_item) = self.items.guard().get_mut(index.current_index()) {
-                    selected_item.select(true);
+            Self::Input::Paste => {
+                let clipboard = root.clipboard();
+                relm4::spawn_local(async move {
+                    if let Some((mode, paths)) = clipboard::read_files(&clipboard).await {
+                        sender.input(ItemsBoxInput::PasteFiles(mode, paths));
+                    }
+                });
+            }
+            Self::Input::PasteFiles(mode, paths) => {
+                // Cut files can only be pasted once, like in other file managers.
+                if mode == TransferMode::Move {
+                    clipboard::clear(&root.clipboard());
+                }
+                let operation = Operation::Transfer(mode, paths, self.current_dir.clone().into());
+                let _ = sender.output(Self::Output::StartJob(operation));
+            }
+            Self::Input::TrashSelection => {
+                let paths = self.selected_paths();
+                if !paths.is_empty() {
+                    let _ = sender.output(Self::Output::StartJob(Operation::Trash(paths)));
+                }
+            }
+            Self::Input::DeleteSelection => {
+                let paths = self.selected_paths();
+                if paths.is_empty() {
+                    return;
+                }
+                sender.spawn_oneshot_command(move || {
+                    let (bytes, files) = paths
+                        .iter()
+                        .map(|path| tree_size(path))
+                        .fold((0, 0), |(bytes, files), (b, f)| (bytes + b, files + f));
+                    ItemsBoxCommand::DeleteMeasured(paths, bytes, files)
+                });
+            }
+            Self::Input::StartRename => match &self.items.selected()[..] {
+                [] => {}
+                [entry] => self.start_rename(entry, root, &sender),
+                entries => {
+                    let batch_rename = BatchRename::builder()
+                        .launch(BatchRename::init(entries.to_vec()))
+                        .forward(sender.input_sender(), |output| match output {
+                            BatchRenameOutput::Rename(paths, names) => {
+                                ItemsBoxInput::Rename(paths, names)
+                            }
+                        });
+                    let window = batch_rename.widget();
+                    window.set_transient_for(root.root().and_downcast_ref::<gtk::Window>());
+                    window.present();
+                    self.batch_rename = Some(batch_rename);
+                }
+            },
+            Self::Input::Rename(paths, names) => {
+                let unchanged = paths
+                    .iter()
+                    .zip(&names)
+                    .all(|(path, name)| path.file_name() == Some(OsStr::new(name)));
+                if !unchanged {
+                    let _ = sender.output(Self::Output::StartJob(Operation::Rename(paths, names)));
                 }
             }
+            Self::Input::Create(entry) => {
+                let path = entry.free_path(Path::new(&self.current_dir));
+                self.pending_rename = Some(path.clone());
+                let _ = sender.output(Self::Output::StartJob(Operation::Create(path, entry)));
+            }
+            Self::Input::ShowContextMenu(x, y) => {
+                sender.spawn_oneshot_command(move || {
+                    ItemsBoxCommand::TemplatesRead(templates(), x, y)
+                });
+            }
             Self::Input::ToggleShowHiddenItems => {
                 self.set_show_hidden_items(!self.show_hidden_items);
-                sender.input(Self::Input::LoadDirectory(self.current_dir.clone()));
+                self.items.set_show_hidden_items(self.show_hidden_items);
             }
             Self::Input::ShowHiddenItems(show) => {
                 self.set_show_hidden_items(show);
-                if self.changed(ItemsBox::show_hidden_items()) {
-                    sender.input(Self::Input::LoadDirectory(self.current_dir.clone()))
-                }
+                self.items.set_show_hidden_items(show);
+            }
+            Self::Input::SetViewMode(view_mode) => self.set_view_mode(view_mode),
+            Self::Input::GrabFocus => {
+                let _ = match self.view_mode {
+                    ViewMode::List => self.items.list_view().grab_focus(),
+                    ViewMode::Grid => self.items.grid_view().grab_focus(),
+                    ViewMode::Details => self.items.column_view().grab_focus(),
+                };
+            }
+            Self::Input::SetZoom(zoom) => self.set_zoom(zoom),
+            Self::Input::SetColumnVisible(column, visible) => {
+                self.items.set_column_visible(column, visible);
+            }
+            Self::Input::SortBy(key) => {
+                let sorting = Sorting {
+                    key,
+                    ..self.sorting
+                };
+                self.sort(sorting, &sender);
+            }
+            Self::Input::SetSortOption(option, value) => {
+                self.sort(self.sorting.with_option(option, value), &sender);
+            }
+            Self::Input::Sort(key, descending) => {
+                let sorting = Sorting {
+                    key,
+                    descending,
+                    ..self.sorting
+                };
+                self.sort(sorting, &sender);
             }
         }
     }
-}
 
-fn convert_item_response(output: ItemOutput) -> ItemsBoxInput {
-    match output {
-        ItemOutput::OpenFile(path) => ItemsBoxInput::OpenFile(path),
-        ItemOutput::OpenDirectory(path) => ItemsBoxInput::LoadDirectory(path),
-        ItemOutput::ItemSelected(index) => ItemsBoxInput::SelectItem(index),
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        self.reset();
+        if message
+            .load_id()
+            .is_some_and(|load_id| load_id != self.load_id)
+        {
+            return;
+        }
+
+        match message {
+            ItemsBoxCommand::DirectoryOpened(_, current_dir) => {
+                self.watcher = DirectoryWatcher::new(&current_dir, {
+                    let sender = sender.clone();
+                    move |paths| sender.input(ItemsBoxInput::ItemsChanged(paths))
+                });
+                self.deferred_changes.clear();
+                self.pending_rename = None;
+                self.set_current_dir(current_dir.clone());
+                self.items.clear();
+                self.update_history(current_dir.clone(), &sender);
+                let _ = sender.output(Self::Output::DirectoryLoaded(current_dir));
+            }
+            ItemsBoxCommand::ItemsLoaded(_, items) => self.append_items(items),
+            ItemsBoxCommand::ItemsChanged(_, changes) => {
+                let created = changes
+                    .iter()
+                    .find(|(path, item)| {
+                        item.is_some() && self.pending_rename.as_ref() == Some(path)
+                    })
+                    .map(|(path, _)| path.clone());
+                let changes = changes
+                    .into_iter()
+                    .map(|(path, item)| {
+                        let item = item.map(|(entry, content_type)| Item::new(entry, content_type));
+                        (path, item)
+                    })
+                    .collect();
+                self.items.update(changes);
+                if let Some(path) = created {
+                    self.pending_rename = None;
+                    self.items.select_path(&path);
+                    // The row of the new item is only bound once the view is laid out again.
+                    glib::idle_add_local_once(move || sender.input(ItemsBoxInput::StartRename));
+                }
+            }
+            ItemsBoxCommand::LoadingFinished(_) => {
+                self.loading_task = None;
+                self.set_loading(false);
+                if let Some(path) = self.pending_selection.take() {
+                    self.items.select_path(&path);
+                }
+                let changes = std::mem::take(&mut self.deferred_changes);
+                self.refresh_items(changes, &sender);
+            }
+            ItemsBoxCommand::LoadingFailed(_, error) => {
+                self.loading_task = None;
+                self.pending_selection = None;
+                self.set_loading(false);
+                let changes = std::mem::take(&mut self.deferred_changes);
+                self.refresh_items(changes, &sender);
+                let _ = sender.output(Self::Output::Error(error));
+            }
+            ItemsBoxCommand::TemplatesRead(templates, x, y) => {
+                // Read each time, so that templates added meanwhile show up.
+                if let Some(menu) = self.context_menu.menu_model().and_downcast::<gio::Menu>() {
+                    menu.remove(0);
+                    menu.insert_section(0, None, &new_entry_menu(&templates));
+                }
+                let point = gdk::Rectangle::new(x as i32, y as i32, 1, 1);
+                self.context_menu.set_pointing_to(Some(&point));
+                self.context_menu.popup();
+            }
+            ItemsBoxCommand::ThumbnailLoaded(path, thumbnail) => {
+                self.items.set_thumbnail(&path, thumbnail);
+            }
+            ItemsBoxCommand::DeleteMeasured(paths, bytes, files) => {
+                let message = match &paths[..] {
+                    [path] => format!(
+                        "Permanently delete “{}”?",
+                        path.file_name().unwrap_or_default().to_string_lossy()
+                    ),
+                    paths => format!("Permanently delete {} items?", paths.len()),
+                };
+                let files = match files {
+                    1 => "1 file".to_owned(),
+                    files => format!("{files} files"),
+                };
+                let detail = format!(
+                    "{files} ({}) will be deleted. This cannot be undone.",
+                    glib::format_size(bytes)
+                );
+                confirm(root, &message, &detail, "Delete", move || {
+                    let _ = sender.output(ItemsBoxOutput::StartJob(Operation::Delete(paths)));
+                });
+            }
+        }
     }
 }
@@ -0,0 +1,7 @@
+/// How a directory load moves through the history once the directory is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    Visit,
+    Back(usize),
+    Forward(usize),
+}
@@ -1,5 +1,17 @@
+use super::Sorting;
+use crate::core::Operation;
+
 #[derive(Debug)]
 pub enum ItemsBoxOutput {
     DirectoryLoaded(String),
+    /// The back entries (most recent first) and the forward entries (nearest first).
+    HistoryChanged(Vec<String>, Vec<String>),
+    SortingChanged(Sorting),
+    OpenInNewTab(String),
+    /// The zoom level of the window is to change, set back through `SetZoom`.
+    ZoomIn,
+    ZoomOut,
+    /// Runs a file operation in the background.
+    StartJob(Operation),
     Error(String),
 }
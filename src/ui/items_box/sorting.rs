@@ -0,0 +1,211 @@
+use std::cmp::Ordering;
+
+use super::column::Column;
+use super::item::Item;
+use crate::core::{compare_names, extension, user_name};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Size,
+    Modified,
+    Type,
+    Extension,
+    Permissions,
+    Owner,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 7] = [
+        SortKey::Name,
+        SortKey::Size,
+        SortKey::Modified,
+        SortKey::Type,
+        SortKey::Extension,
+        SortKey::Permissions,
+        SortKey::Owner,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            SortKey::Extension => "extension",
+            key => key.column().map(|column| column.id()).unwrap_or_default(),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.id() == id)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            SortKey::Extension => "Extension",
+            key => key
+                .column()
+                .map(|column| column.title())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The details view column showing this key, if there is one.
+    pub fn column(&self) -> Option<Column> {
+        match self {
+            SortKey::Name => Some(Column::Name),
+            SortKey::Size => Some(Column::Size),
+            SortKey::Modified => Some(Column::Modified),
+            SortKey::Type => Some(Column::Type),
+            SortKey::Extension => None,
+            SortKey::Permissions => Some(Column::Permissions),
+            SortKey::Owner => Some(Column::Owner),
+        }
+    }
+}
+
+impl From<Column> for SortKey {
+    fn from(value: Column) -> Self {
+        match value {
+            Column::Name => SortKey::Name,
+            Column::Size => SortKey::Size,
+            Column::Modified => SortKey::Modified,
+            Column::Type => SortKey::Type,
+            Column::Permissions => SortKey::Permissions,
+            Column::Owner => SortKey::Owner,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOption {
+    Descending,
+    FoldersFirst,
+    Natural,
+    CaseInsensitive,
+}
+
+impl SortOption {
+    pub const ALL: [SortOption; 4] = [
+        SortOption::Descending,
+        SortOption::FoldersFirst,
+        SortOption::Natural,
+        SortOption::CaseInsensitive,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            SortOption::Descending => "descending",
+            SortOption::FoldersFirst => "folders_first",
+            SortOption::Natural => "natural",
+            SortOption::CaseInsensitive => "case_insensitive",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            SortOption::Descending => "Reverse Order",
+            SortOption::FoldersFirst => "Folders First",
+            SortOption::Natural => "Natural Order",
+            SortOption::CaseInsensitive => "Ignore Case",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sorting {
+    pub key: SortKey,
+    pub descending: bool,
+    pub folders_first: bool,
+    pub natural: bool,
+    pub case_insensitive: bool,
+}
+
+impl Default for Sorting {
+    fn default() -> Self {
+        Self {
+            key: SortKey::Name,
+            descending: false,
+            folders_first: true,
+            natural: true,
+            case_insensitive: true,
+        }
+    }
+}
+
+impl Sorting {
+    pub fn option(&self, option: SortOption) -> bool {
+        match option {
+            SortOption::Descending => self.descending,
+            SortOption::FoldersFirst => self.folders_first,
+            SortOption::Natural => self.natural,
+            SortOption::CaseInsensitive => self.case_insensitive,
+        }
+    }
+
+    pub fn with_option(self, option: SortOption, value: bool) -> Self {
+        match option {
+            SortOption::Descending => Self {
+                descending: value,
+                ..self
+            },
+            SortOption::FoldersFirst => Self {
+                folders_first: value,
+                ..self
+            },
+            SortOption::Natural => Self {
+                natural: value,
+                ..self
+            },
+            SortOption::CaseInsensitive => Self {
+                case_insensitive: value,
+                ..self
+            },
+        }
+    }
+
+    /// With folders first, directories stay in front and the order is only reversed
+    /// within each group.
+    pub fn compare(&self, a: &Item, b: &Item) -> Ordering {
+        let folders = if self.folders_first {
+            (!a.entry().is_dir()).cmp(&!b.entry().is_dir())
+        } else {
+            Ordering::Equal
+        };
+
+        folders.then_with(|| {
+            let ordering = self
+                .compare_keys(a, b)
+                .then_with(|| self.compare_names(a, b));
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        })
+    }
+
+    fn compare_keys(&self, a: &Item, b: &Item) -> Ordering {
+        let (first, second) = (a.entry(), b.entry());
+        match self.key {
+            SortKey::Name => Ordering::Equal,
+            SortKey::Size => first.size().cmp(&second.size()),
+            SortKey::Modified => first.modified().cmp(&second.modified()),
+            SortKey::Type => a.content_type().cmp(b.content_type()),
+            SortKey::Extension => {
+                compare_names(extension(a.name()), extension(b.name()), false, true)
+            }
+            SortKey::Permissions => first.permissions().cmp(&second.permissions()),
+            SortKey::Owner => {
+                (user_name(first.uid()), first.uid()).cmp(&(user_name(second.uid()), second.uid()))
+            }
+        }
+    }
+
+    fn compare_names(&self, a: &Item, b: &Item) -> Ordering {
+        if self.case_insensitive {
+            a.collation_key(self.natural)
+                .cmp(b.collation_key(self.natural))
+                .then_with(|| compare_names(a.name(), b.name(), self.natural, true))
+        } else {
+            compare_names(a.name(), b.name(), self.natural, false)
+        }
+    }
+}
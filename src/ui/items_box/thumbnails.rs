@@ -0,0 +1,336 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use relm4::gtk::gdk_pixbuf::{Colorspace, InterpType, Pixbuf};
+use relm4::gtk::{gdk, glib};
+
+use crate::core::{DirEntryInfo, EntryType, Thumbnailer, load_thumbnailers, thumbnail_cache_dir};
+
+/// Edge of a `normal` thumbnail in the freedesktop cache.
+const NORMAL_SIZE: i32 = 128;
+const SOFTWARE: &str = "file_explorer";
+const MAX_WORKERS: usize = 4;
+/// How long a thumbnailer may run before it is killed, so a hung one does not keep
+/// a worker for good.
+const THUMBNAILER_TIMEOUT: Duration = Duration::from_secs(30);
+
+static TEMPORARY_ID: AtomicUsize = AtomicUsize::new(0);
+static NEXT_OWNER: AtomicUsize = AtomicUsize::new(0);
+static POOL: OnceLock<ThumbnailPool> = OnceLock::new();
+
+type OnReady = Arc<dyn Fn(PathBuf, gdk::Texture) + Send + Sync>;
+
+struct Request {
+    owner: usize,
+    on_ready: OnReady,
+    path: PathBuf,
+    content_type: String,
+    mtime: u64,
+}
+
+#[derive(Default)]
+struct Queue {
+    requests: VecDeque<Request>,
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    available: Condvar,
+}
+
+struct Generator {
+    cache_dir: Option<PathBuf>,
+    pixbuf_types: HashSet<String>,
+    thumbnailers: Vec<Thumbnailer>,
+}
+
+/// Looks up and generates thumbnails following the freedesktop Thumbnail Managing
+/// spec on a few worker threads shared by the whole application.
+struct ThumbnailPool {
+    shared: Arc<Shared>,
+    generator: Arc<Generator>,
+}
+
+impl ThumbnailPool {
+    fn get() -> &'static ThumbnailPool {
+        POOL.get_or_init(ThumbnailPool::new)
+    }
+
+    fn new() -> Self {
+        let pixbuf_types = Pixbuf::formats()
+            .into_iter()
+            .filter(|format| !format.is_disabled())
+            .flat_map(|format| format.mime_types())
+            .map(String::from)
+            .collect();
+        let generator = Arc::new(Generator {
+            cache_dir: thumbnail_cache_dir(),
+            pixbuf_types,
+            thumbnailers: load_thumbnailers(),
+        });
+        let shared = Arc::new(Shared::default());
+
+        let workers = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+            .min(MAX_WORKERS);
+        for index in 0..workers {
+            let (shared, generator) = (shared.clone(), generator.clone());
+            let _ = thread::Builder::new()
+                .name(format!("thumbnailer-{index}"))
+                .spawn(move || {
+                    loop {
+                        let request = shared.next_request();
+                        if let Some(texture) = generator.thumbnail(&request) {
+                            (request.on_ready)(request.path, texture);
+                        }
+                    }
+                });
+        }
+
+        Self { shared, generator }
+    }
+}
+
+/// The thumbnails of one items list, reported through `on_ready` as they are ready.
+pub struct Thumbnails {
+    owner: usize,
+    on_ready: OnReady,
+}
+
+impl Thumbnails {
+    pub fn new(on_ready: impl Fn(PathBuf, gdk::Texture) + Send + Sync + 'static) -> Self {
+        Self {
+            owner: NEXT_OWNER.fetch_add(1, Ordering::Relaxed),
+            on_ready: Arc::new(on_ready),
+        }
+    }
+
+    pub fn request(&self, entry: &DirEntryInfo, content_type: &str) {
+        let pool = ThumbnailPool::get();
+        if entry.target_type() != Some(EntryType::File)
+            || !pool.generator.supports(content_type)
+            || pool
+                .generator
+                .cache_dir
+                .as_ref()
+                .is_some_and(|cache_dir| entry.path().starts_with(cache_dir))
+        {
+            return;
+        }
+
+        let mtime = entry
+            .modified()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or_default();
+        let mut queue = pool.shared.queue.lock().unwrap();
+        queue.requests.push_back(Request {
+            owner: self.owner,
+            on_ready: self.on_ready.clone(),
+            path: entry.path().to_path_buf(),
+            content_type: content_type.to_owned(),
+            mtime,
+        });
+        pool.shared.available.notify_one();
+    }
+
+    /// Drops the requests no worker has started yet, e.g. after leaving the directory.
+    pub fn cancel_pending(&self) {
+        let Some(pool) = POOL.get() else {
+            return;
+        };
+        let mut queue = pool.shared.queue.lock().unwrap();
+        queue.requests.retain(|request| request.owner != self.owner);
+    }
+}
+
+impl Drop for Thumbnails {
+    fn drop(&mut self) {
+        self.cancel_pending();
+    }
+}
+
+impl Shared {
+    fn next_request(&self) -> Request {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if let Some(request) = queue.requests.pop_front() {
+                return request;
+            }
+            queue = self.available.wait(queue).unwrap();
+        }
+    }
+}
+
+impl Generator {
+    fn supports(&self, content_type: &str) -> bool {
+        self.pixbuf_types.contains(content_type)
+            || self
+                .thumbnailers
+                .iter()
+                .any(|thumbnailer| thumbnailer.mime_types().iter().any(|t| t == content_type))
+    }
+
+    fn thumbnail(&self, request: &Request) -> Option<gdk::Texture> {
+        let uri = glib::filename_to_uri(&request.path, None).ok()?;
+        let hash = glib::compute_checksum_for_string(glib::ChecksumType::Md5, uri.as_str())?;
+        let name = format!("{hash}.png");
+        let mtime = request.mtime.to_string();
+
+        if let Some(cache_dir) = &self.cache_dir {
+            for size in ["normal", "large"] {
+                if let Some(pixbuf) = load_valid(&cache_dir.join(size).join(&name), &uri, &mtime) {
+                    return Some(gdk::Texture::for_pixbuf(&pixbuf));
+                }
+            }
+            let failure = cache_dir.join("fail").join(SOFTWARE).join(&name);
+            if load_valid(&failure, &uri, &mtime).is_some() {
+                return None;
+            }
+        }
+
+        let Some(pixbuf) = self.generate(request, &uri, &hash) else {
+            let marker = Pixbuf::new(Colorspace::Rgb, true, 8, 1, 1)?;
+            marker.fill(0);
+            self.save(
+                &marker,
+                &Path::new("fail").join(SOFTWARE),
+                &name,
+                &uri,
+                &mtime,
+            );
+            return None;
+        };
+        self.save(&pixbuf, Path::new("normal"), &name, &uri, &mtime);
+        Some(gdk::Texture::for_pixbuf(&pixbuf))
+    }
+
+    fn generate(&self, request: &Request, uri: &str, hash: &str) -> Option<Pixbuf> {
+        if self.pixbuf_types.contains(&request.content_type) {
+            let (_, width, height) = Pixbuf::file_info(&request.path)?;
+            let pixbuf = if width <= NORMAL_SIZE && height <= NORMAL_SIZE {
+                Pixbuf::from_file(&request.path)
+            } else {
+                Pixbuf::from_file_at_scale(&request.path, NORMAL_SIZE, NORMAL_SIZE, true)
+            }
+            .ok()?;
+            return Some(pixbuf.apply_embedded_orientation().unwrap_or(pixbuf));
+        }
+
+        let thumbnailer = self.thumbnailers.iter().find(|thumbnailer| {
+            thumbnailer
+                .mime_types()
+                .iter()
+                .any(|mime_type| *mime_type == request.content_type)
+        })?;
+        let output = std::env::temp_dir().join(format!("{}.png", temporary_name(hash)));
+        let command = thumbnailer.command(&request.path, uri, &output, NORMAL_SIZE as u32);
+        let status = run_with_timeout(Command::new(&command[0]).args(&command[1..]));
+        let pixbuf = status
+            .is_some_and(|status| status.success())
+            .then(|| Pixbuf::from_file(&output).ok())
+            .flatten();
+        let _ = fs::remove_file(&output);
+        pixbuf.map(fit)
+    }
+
+    /// Writes the thumbnail to a temporary file first and renames it, so other
+    /// programs reading the shared cache never see a partial PNG.
+    fn save(&self, pixbuf: &Pixbuf, dir: &Path, name: &str, uri: &str, mtime: &str) {
+        let Some(cache_dir) = &self.cache_dir else {
+            return;
+        };
+        let dir = cache_dir.join(dir);
+        if fs::create_dir_all(&dir).is_err() {
+            return;
+        }
+        set_mode(cache_dir, 0o700);
+        set_mode(&dir, 0o700);
+
+        let temporary = dir.join(temporary_name(name));
+        let options = [
+            ("tEXt::Thumb::URI", uri),
+            ("tEXt::Thumb::MTime", mtime),
+            ("tEXt::Software", SOFTWARE),
+        ];
+        if pixbuf.savev(&temporary, "png", &options).is_ok() {
+            set_mode(&temporary, 0o600);
+            if fs::rename(&temporary, dir.join(name)).is_ok() {
+                return;
+            }
+        }
+        let _ = fs::remove_file(&temporary);
+    }
+}
+
+/// A cached thumbnail is only valid for the same URI and modification time.
+fn load_valid(path: &Path, uri: &str, mtime: &str) -> Option<Pixbuf> {
+    let pixbuf = Pixbuf::from_file(path).ok()?;
+    let matches = |key: &str, expected: &str| {
+        pixbuf
+            .option(key)
+            .is_some_and(|value| value.as_str() == expected)
+    };
+    (matches("tEXt::Thumb::URI", uri) && matches("tEXt::Thumb::MTime", mtime)).then_some(pixbuf)
+}
+
+/// Scales down output of thumbnailers that ignore the requested size.
+fn fit(pixbuf: Pixbuf) -> Pixbuf {
+    let (width, height) = (pixbuf.width(), pixbuf.height());
+    if width <= NORMAL_SIZE && height <= NORMAL_SIZE {
+        return pixbuf;
+    }
+    let scale = NORMAL_SIZE as f64 / width.max(height) as f64;
+    let (width, height) = (
+        ((width as f64 * scale) as i32).max(1),
+        ((height as f64 * scale) as i32).max(1),
+    );
+    pixbuf
+        .scale_simple(width, height, InterpType::Bilinear)
+        .unwrap_or(pixbuf)
+}
+
+/// Runs a thumbnailer, killing it once it takes longer than `THUMBNAILER_TIMEOUT`.
+fn run_with_timeout(command: &mut Command) -> Option<ExitStatus> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let deadline = Instant::now() + THUMBNAILER_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait().ok()? {
+            return Some(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn temporary_name(name: &str) -> String {
+    let id = TEMPORARY_ID.fetch_add(1, Ordering::Relaxed);
+    format!("{name}.{}-{id}.tmp", process::id())
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode));
+}
+
+#[cfg(windows)]
+fn set_mode(_: &Path, _: u32) {}
@@ -0,0 +1,30 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewMode {
+    List,
+    Grid,
+    Details,
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 3] = [ViewMode::List, ViewMode::Grid, ViewMode::Details];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            ViewMode::List => "list",
+            ViewMode::Grid => "grid",
+            ViewMode::Details => "details",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.id() == id)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ViewMode::List => "List",
+            ViewMode::Grid => "Icons",
+            ViewMode::Details => "Details",
+        }
+    }
+}
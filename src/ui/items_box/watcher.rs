@@ -0,0 +1,58 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use relm4::gtk::{gio, glib, prelude::*};
+
+const COALESCE_DELAY: Duration = Duration::from_millis(150);
+
+/// Watches a directory and reports the changed paths in batches, so a burst of
+/// events (e.g. a `git checkout`) results in a single update.
+pub struct DirectoryWatcher {
+    monitor: gio::FileMonitor,
+}
+
+impl DirectoryWatcher {
+    pub fn new(dir: &str, on_changed: impl Fn(Vec<PathBuf>) + 'static) -> Option<Self> {
+        let monitor = gio::File::for_path(dir)
+            .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+            .ok()?;
+
+        let pending = Rc::new(RefCell::new(HashSet::new()));
+        let on_changed = Rc::new(on_changed);
+        monitor.connect_changed(move |_, file, other_file, event| {
+            let files = match event {
+                gio::FileMonitorEvent::Renamed => [Some(file), other_file],
+                gio::FileMonitorEvent::Created
+                | gio::FileMonitorEvent::Deleted
+                | gio::FileMonitorEvent::Changed
+                | gio::FileMonitorEvent::ChangesDoneHint
+                | gio::FileMonitorEvent::AttributeChanged
+                | gio::FileMonitorEvent::MovedIn
+                | gio::FileMonitorEvent::MovedOut => [Some(file), None],
+                _ => return,
+            };
+
+            let schedule = pending.borrow().is_empty();
+            pending
+                .borrow_mut()
+                .extend(files.into_iter().flatten().filter_map(|file| file.path()));
+            if schedule {
+                let (pending, on_changed) = (pending.clone(), on_changed.clone());
+                glib::timeout_add_local_once(COALESCE_DELAY, move || {
+                    on_changed(pending.take().into_iter().collect());
+                });
+            }
+        });
+
+        Some(Self { monitor })
+    }
+}
+
+impl Drop for DirectoryWatcher {
+    fn drop(&mut self) {
+        self.monitor.cancel();
+    }
+}
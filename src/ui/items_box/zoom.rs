@@ -0,0 +1,26 @@
+/// One of the fixed zoom levels of the items views, applied through `zoom-<level>`
+/// CSS classes that scale icons and text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zoom(usize);
+
+impl Zoom {
+    const CSS_CLASSES: [&'static str; 5] = ["zoom-0", "zoom-1", "zoom-2", "zoom-3", "zoom-4"];
+
+    pub fn zoom_in(self) -> Self {
+        Self((self.0 + 1).min(Self::CSS_CLASSES.len() - 1))
+    }
+
+    pub fn zoom_out(self) -> Self {
+        Self(self.0.saturating_sub(1))
+    }
+
+    pub fn css_class(&self) -> &'static str {
+        Self::CSS_CLASSES[self.0]
+    }
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self(2)
+    }
+}
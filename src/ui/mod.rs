@@ -1,2 +1,10 @@
+pub(crate) mod batch_rename;
+pub(crate) mod dialogs;
+pub(crate) mod history;
 pub(crate) mod items_box;
+pub(crate) mod panes;
+pub(crate) mod path_bar;
+pub(crate) mod sidebar;
+pub(crate) mod tabs;
 pub(crate) mod top_panel;
+pub(crate) mod trash;
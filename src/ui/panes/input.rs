@@ -0,0 +1,19 @@
+use crate::core::TransferMode;
+use crate::ui::items_box::Zoom;
+use crate::ui::tabs::{TabsInput, TabsOutput};
+
+#[derive(Debug)]
+pub enum PanesInput {
+    ToggleSplit,
+    SwitchPane,
+    ActivatePane(usize),
+    /// Loads the current directory of the active pane in the other one.
+    SyncOtherPane,
+    TransferToOtherPane(TransferMode),
+    /// Applies the zoom level of the window to all panes.
+    SetZoom(Zoom),
+    /// An output of the tabs of the pane with the given id.
+    PaneOutput(usize, TabsOutput),
+    /// A message for the tabs of the active pane.
+    Current(TabsInput),
+}
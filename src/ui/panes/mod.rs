@@ -0,0 +1,243 @@
+pub mod init;
+mod input;
+mod output;
+
+use init::PanesInit;
+pub(crate) use input::PanesInput;
+pub(crate) use output::PanesOutput;
+use relm4::gtk::prelude::*;
+use relm4::prelude::*;
+
+use crate::ui::items_box::{ItemsBoxInput, Zoom};
+use crate::ui::tabs::{Tabs, TabsInput, TabsOutput};
+
+struct Pane {
+    id: usize,
+    tabs: Controller<Tabs>,
+    current_dir: String,
+    show_hidden_items: bool,
+}
+
+/// One pane, or two side by side of which the active one receives the commands.
+pub struct Panes {
+    paned: gtk::Paned,
+    panes: Vec<Pane>,
+    active: usize,
+    next_id: usize,
+    zoom: Zoom,
+}
+
+impl Panes {
+    pub fn init(current_dir: &str, show_hidden_items: bool) -> PanesInit {
+        PanesInit::new(current_dir.to_owned(), show_hidden_items)
+    }
+
+    fn open_pane(
+        &mut self,
+        current_dir: &str,
+        show_hidden_items: bool,
+        sender: &ComponentSender<Self>,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let tabs = Tabs::builder()
+            .launch(Tabs::init(current_dir, show_hidden_items))
+            .forward(sender.input_sender(), move |output| {
+                PanesInput::PaneOutput(id, output)
+            });
+        tabs.emit(TabsInput::SetZoom(self.zoom));
+
+        let focus = gtk::EventControllerFocus::new();
+        focus.connect_enter({
+            let sender = sender.clone();
+            move |_| sender.input(PanesInput::ActivatePane(id))
+        });
+        tabs.widget().add_controller(focus);
+
+        let click = gtk::GestureClick::new();
+        click.set_button(0);
+        click.set_propagation_phase(gtk::PropagationPhase::Capture);
+        click.connect_pressed({
+            let sender = sender.clone();
+            move |_, _, _, _| sender.input(PanesInput::ActivatePane(id))
+        });
+        tabs.widget().add_controller(click);
+
+        self.panes.push(Pane {
+            id,
+            tabs,
+            current_dir: current_dir.to_owned(),
+            show_hidden_items,
+        });
+        self.layout();
+    }
+
+    fn layout(&self) {
+        self.paned.set_start_child(None::<&gtk::Widget>);
+        self.paned.set_end_child(None::<&gtk::Widget>);
+        self.paned
+            .set_start_child(self.panes.first().map(|pane| pane.tabs.widget()));
+        self.paned
+            .set_end_child(self.panes.get(1).map(|pane| pane.tabs.widget()));
+        if self.panes.len() > 1 && self.paned.width() > 0 {
+            self.paned.set_position(self.paned.width() / 2);
+        }
+        self.highlight();
+    }
+
+    fn highlight(&self) {
+        let split = self.panes.len() > 1;
+        for (position, pane) in self.panes.iter().enumerate() {
+            pane.tabs
+                .widget()
+                .set_class_active("active-pane", split && position == self.active);
+        }
+    }
+
+    /// Makes the pane at `position` the active one, which reports its state again.
+    fn activate(&mut self, position: usize) {
+        if position == self.active || position >= self.panes.len() {
+            return;
+        }
+        self.active = position;
+        self.highlight();
+        self.panes[position].tabs.emit(TabsInput::TabSelected);
+    }
+
+    fn active(&self) -> &Pane {
+        &self.panes[self.active]
+    }
+
+    fn other(&self) -> Option<&Pane> {
+        self.panes.get(1 - self.active)
+    }
+
+    fn close_pane(&mut self, position: usize, sender: &ComponentSender<Self>) {
+        self.panes.remove(position);
+        self.active = 0;
+        self.layout();
+        self.active().tabs.emit(TabsInput::TabSelected);
+        let _ = sender.output(PanesOutput::SplitChanged(false));
+    }
+}
+
+#[relm4::component(pub)]
+impl Component for Panes {
+    type Init = PanesInit;
+    type Input = PanesInput;
+    type Output = PanesOutput;
+    type CommandOutput = ();
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let mut model = Self {
+            paned: gtk::Paned::new(gtk::Orientation::Horizontal),
+            panes: Vec::new(),
+            active: 0,
+            next_id: 0,
+            zoom: Zoom::default(),
+        };
+        model.open_pane(init.current_dir(), init.show_hidden_items(), &sender);
+
+        let paned = &model.paned;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    view! {
+        gtk::Box {
+            set_vexpand: true,
+
+            #[local_ref]
+            paned -> gtk::Paned {
+                set_hexpand: true,
+                set_wide_handle: true,
+                set_shrink_start_child: false,
+                set_shrink_end_child: false,
+            },
+        }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _: &Self::Root) {
+        match message {
+            PanesInput::ToggleSplit => {
+                if self.other().is_some() {
+                    self.close_pane(1 - self.active, &sender);
+                } else {
+                    let pane = self.active();
+                    let (current_dir, show_hidden_items) =
+                        (pane.current_dir.clone(), pane.show_hidden_items);
+                    self.open_pane(&current_dir, show_hidden_items, &sender);
+                    let _ = sender.output(PanesOutput::SplitChanged(true));
+                }
+            }
+            PanesInput::SwitchPane => {
+                if self.other().is_some() {
+                    self.activate(1 - self.active);
+                    let focus = TabsInput::Current(ItemsBoxInput::GrabFocus);
+                    self.active().tabs.emit(focus);
+                }
+            }
+            PanesInput::ActivatePane(id) => {
+                if let Some(position) = self.panes.iter().position(|pane| pane.id == id) {
+                    self.activate(position);
+                }
+            }
+            PanesInput::SyncOtherPane => {
+                if let Some(other) = self.other() {
+                    let current_dir = self.active().current_dir.clone();
+                    let load = TabsInput::Current(ItemsBoxInput::LoadDirectory(current_dir));
+                    other.tabs.emit(load);
+                }
+            }
+            PanesInput::TransferToOtherPane(mode) => {
+                if let Some(other) = self.other() {
+                    let dest_dir = other.current_dir.clone();
+                    let transfer =
+                        TabsInput::Current(ItemsBoxInput::TransferSelection(mode, dest_dir));
+                    self.active().tabs.emit(transfer);
+                }
+            }
+            PanesInput::SetZoom(zoom) => {
+                self.zoom = zoom;
+                for pane in &self.panes {
+                    pane.tabs.emit(TabsInput::SetZoom(zoom));
+                }
+            }
+            PanesInput::PaneOutput(id, output) => {
+                let Some(position) = self.panes.iter().position(|pane| pane.id == id) else {
+                    return;
+                };
+                let split = self.panes.len() > 1;
+                let pane = &mut self.panes[position];
+                match &output {
+                    TabsOutput::DirectoryLoaded(current_dir) => {
+                        pane.current_dir = current_dir.clone();
+                    }
+                    TabsOutput::HiddenItemsChanged(show) => pane.show_hidden_items = *show,
+                    TabsOutput::Closed if split => {
+                        self.close_pane(position, &sender);
+                        return;
+                    }
+                    _ => {}
+                }
+                let global = matches!(
+                    output,
+                    TabsOutput::Error(_)
+                        | TabsOutput::StartJob(_)
+                        | TabsOutput::ZoomIn
+                        | TabsOutput::ZoomOut
+                );
+                if position == self.active || global {
+                    let _ = sender.output(PanesOutput::Tab(output));
+                }
+            }
+            PanesInput::Current(message) => self.active().tabs.emit(message),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use crate::ui::tabs::TabsOutput;
+
+#[derive(Debug)]
+pub enum PanesOutput {
+    /// The state of the selected tab of the active pane.
+    Tab(TabsOutput),
+    SplitChanged(bool),
+}
@@ -0,0 +1,8 @@
+#[derive(Debug)]
+pub enum PathBarInput {
+    SetDirectory(String),
+    StartEditing,
+    StopEditing,
+    Submit(String),
+    Complete(String),
+}
@@ -0,0 +1,271 @@
+pub mod init;
+mod input;
+mod output;
+
+use std::fs;
+use std::path::Path;
+
+use init::PathBarInit;
+pub(crate) use input::PathBarInput;
+pub(crate) use output::PathBarOutput;
+use relm4::gtk::{gdk, glib, prelude::*};
+use relm4::prelude::*;
+
+use crate::core::{
+    complete_directory, expand_path, list_directories, normalize_path, path_segments,
+};
+
+const CRUMBS: &str = "crumbs";
+const ENTRY: &str = "entry";
+
+#[tracker::track]
+pub struct PathBar {
+    current_dir: String,
+    editing: bool,
+    error: Option<String>,
+    candidates: Vec<String>,
+    #[tracker::do_not_track]
+    crumbs: gtk::Box,
+}
+
+impl PathBar {
+    pub fn init(current_dir: &str) -> PathBarInit {
+        PathBarInit::new(current_dir.to_owned())
+    }
+
+    fn hint(&self) -> String {
+        match &self.error {
+            Some(error) => error.clone(),
+            None => self.candidates.join("  "),
+        }
+    }
+
+    fn rebuild_crumbs(&self, sender: &ComponentSender<Self>) {
+        while let Some(child) = self.crumbs.first_child() {
+            self.crumbs.remove(&child);
+        }
+
+        let segments = path_segments(&self.current_dir);
+        let last = segments.len().saturating_sub(1);
+        for (index, (label, path)) in segments.into_iter().enumerate() {
+            let button = gtk::Button::with_label(&label);
+            if index == last {
+                button.add_css_class("current-crumb");
+            }
+            button.connect_clicked({
+                let sender = sender.clone();
+                let path = path.clone();
+                move |_| {
+                    let _ = sender.output(PathBarOutput::Navigate(path.clone()));
+                }
+            });
+            self.crumbs.append(&button);
+
+            if let Some(parent) = Path::new(&path).parent() {
+                let siblings = gtk::MenuButton::new();
+                siblings.set_icon_name("pan-down-symbolic");
+                siblings.set_tooltip_text(Some("Folders Next to This One"));
+                siblings.set_create_popup_func({
+                    let sender = sender.clone();
+                    let parent = parent.to_path_buf();
+                    move |menu_button| {
+                        menu_button.set_popover(Some(&siblings_popover(&parent, &sender)));
+                    }
+                });
+                self.crumbs.append(&siblings);
+            }
+        }
+    }
+}
+
+/// A popover listing the subdirectories of `dir`; choosing one navigates there.
+fn siblings_popover(dir: &Path, sender: &ComponentSender<PathBar>) -> gtk::Popover {
+    let list = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    let popover = gtk::Popover::new();
+    for path in list_directories(dir, false) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let button = gtk::Button::with_label(&name);
+        button.set_has_frame(false);
+        if let Some(label) = button.child().and_downcast::<gtk::Label>() {
+            label.set_xalign(0.0);
+        }
+        button.connect_clicked({
+            let sender = sender.clone();
+            let popover = popover.downgrade();
+            let path = path.display().to_string();
+            move |_| {
+                if let Some(popover) = popover.upgrade() {
+                    popover.popdown();
+                }
+                let _ = sender.output(PathBarOutput::Navigate(path.clone()));
+            }
+        });
+        list.append(&button);
+    }
+
+    let scrolled_window = gtk::ScrolledWindow::new();
+    scrolled_window.set_hscrollbar_policy(gtk::PolicyType::Never);
+    scrolled_window.set_max_content_height(400);
+    scrolled_window.set_propagate_natural_height(true);
+    scrolled_window.set_child(Some(&list));
+    popover.set_child(Some(&scrolled_window));
+    popover
+}
+
+#[relm4::component(pub)]
+impl Component for PathBar {
+    type Init = PathBarInit;
+    type Input = PathBarInput;
+    type Output = PathBarOutput;
+    type CommandOutput = ();
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            current_dir: init.current_dir().to_owned(),
+            editing: false,
+            error: None,
+            candidates: Vec::new(),
+            crumbs: gtk::Box::new(gtk::Orientation::Horizontal, 0),
+            tracker: 0,
+        };
+        model.rebuild_crumbs(&sender);
+
+        let crumbs = &model.crumbs;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+            set_hexpand: true,
+            set_valign: gtk::Align::Center,
+
+            gtk::Stack {
+                add_named[Some(CRUMBS)] = &gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_spacing: 5,
+
+                    gtk::ScrolledWindow {
+                        set_hexpand: true,
+                        set_vscrollbar_policy: gtk::PolicyType::Never,
+                        set_hscrollbar_policy: gtk::PolicyType::External,
+                        #[local_ref]
+                        crumbs -> gtk::Box {
+                            set_css_classes: &["linked", "path-bar"],
+                        },
+                    },
+
+                    gtk::Button {
+                        set_icon_name: "document-edit-symbolic",
+                        set_tooltip_text: Some("Edit Location"),
+                        connect_clicked => PathBarInput::StartEditing,
+                    },
+                },
+                #[name = "entry"]
+                add_named[Some(ENTRY)] = &gtk::Entry {
+                    set_hexpand: true,
+                    set_placeholder_text: Some("Type a location, e.g. ~/Documents"),
+                    #[track = "model.changed(PathBar::error())"]
+                    set_class_active: ("error", model.error.is_some()),
+                    connect_activate[sender] => move |entry| {
+                        sender.input(PathBarInput::Submit(entry.text().into()));
+                    },
+                    add_controller = gtk::EventControllerKey {
+                        set_propagation_phase: gtk::PropagationPhase::Capture,
+                        connect_key_pressed[sender] => move |controller, key, _, modifiers| {
+                            if !modifiers.is_empty() {
+                                return glib::Propagation::Proceed;
+                            }
+                            match key {
+                                gdk::Key::Tab => {
+                                    let text = controller
+                                        .widget()
+                                        .and_downcast::<gtk::Entry>()
+                                        .map(|entry| entry.text().to_string())
+                                        .unwrap_or_default();
+                                    sender.input(PathBarInput::Complete(text));
+                                    glib::Propagation::Stop
+                                }
+                                gdk::Key::Escape => {
+                                    sender.input(PathBarInput::StopEditing);
+                                    glib::Propagation::Stop
+                                }
+                                _ => glib::Propagation::Proceed,
+                            }
+                        },
+                    },
+                },
+                #[track = "model.changed(PathBar::editing())"]
+                set_visible_child_name: if model.editing { ENTRY } else { CRUMBS },
+            },
+
+            gtk::Label {
+                set_xalign: 0.0,
+                set_ellipsize: gtk::pango::EllipsizeMode::End,
+                #[track = "model.changed(PathBar::error()) || model.changed(PathBar::candidates())"]
+                set_label: &model.hint(),
+                #[track = "model.changed(PathBar::error())"]
+                set_css_classes: if model.error.is_some() { &["path-hint", "error"] } else { &["path-hint", "dim-label"] },
+                #[track = "model.changed(PathBar::editing()) || model.changed(PathBar::error()) || model.changed(PathBar::candidates())"]
+                set_visible: model.editing && (model.error.is_some() || !model.candidates.is_empty()),
+            },
+        }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        _: &Self::Root,
+    ) {
+        self.reset();
+        match message {
+            PathBarInput::SetDirectory(current_dir) => {
+                self.set_current_dir(current_dir);
+                if self.changed(PathBar::current_dir()) {
+                    self.rebuild_crumbs(&sender);
+                }
+            }
+            PathBarInput::StartEditing => {
+                self.set_editing(true);
+                self.set_error(None);
+                self.set_candidates(Vec::new());
+                widgets.entry.set_text(&self.current_dir);
+                widgets.entry.grab_focus();
+                widgets.entry.select_region(0, -1);
+            }
+            PathBarInput::StopEditing => self.set_editing(false),
+            PathBarInput::Submit(text) => {
+                let path = normalize_path(&Path::new(&self.current_dir).join(expand_path(&text)));
+                let location = path.display().to_string();
+                match fs::metadata(&path) {
+                    Ok(metadata) if metadata.is_dir() => {
+                        self.set_editing(false);
+                        self.set_error(None);
+                        let _ = sender.output(PathBarOutput::Navigate(location));
+                    }
+                    Ok(_) => self.set_error(Some(format!("{location} is not a folder"))),
+                    Err(error) => self.set_error(Some(format!("{location}: {error}"))),
+                }
+            }
+            PathBarInput::Complete(text) => {
+                let completion = complete_directory(&expand_path(&text));
+                self.set_error(None);
+                self.set_candidates(completion.candidates);
+                widgets.entry.set_text(&completion.text);
+                widgets.entry.set_position(-1);
+            }
+        }
+        self.update_view(widgets, sender);
+    }
+}
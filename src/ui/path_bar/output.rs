@@ -0,0 +1,4 @@
+#[derive(Debug)]
+pub enum PathBarOutput {
+    Navigate(String),
+}
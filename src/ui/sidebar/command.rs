@@ -0,0 +1,7 @@
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum SidebarCommand {
+    /// The used fraction, free and total bytes of the filesystems of the places.
+    UsageLoaded(Vec<(PathBuf, f64, u64, u64)>),
+}
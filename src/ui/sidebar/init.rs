@@ -0,0 +1,15 @@
+pub struct SidebarInit {
+    _current_dir: String,
+}
+
+impl SidebarInit {
+    pub fn new(current_dir: String) -> Self {
+        Self {
+            _current_dir: current_dir,
+        }
+    }
+
+    pub fn current_dir(&self) -> &str {
+        &self._current_dir
+    }
+}
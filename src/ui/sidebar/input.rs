@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum SidebarInput {
+    SetDirectory(String),
+    /// Re-reads the places after the mounts or the bookmarks changed.
+    Refresh,
+    Activate(i32),
+    AddBookmark(PathBuf),
+    StartRename(usize),
+    RenameBookmark(usize, String),
+    RemoveBookmark(usize),
+    /// Moves the bookmark with the given index to the bookmark row it was dropped on,
+    /// or to the end.
+    DropBookmark(usize, Option<i32>),
+}
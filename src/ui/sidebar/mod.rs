@@ -0,0 +1,463 @@
+mod command;
+pub mod init;
+mod input;
+mod output;
+mod place;
+
+use std::path::{Path, PathBuf};
+
+use command::SidebarCommand;
+use init::SidebarInit;
+pub(crate) use input::SidebarInput;
+pub(crate) use output::SidebarOutput;
+use place::{Place, heading_row, user_dir_icon};
+use relm4::gtk::{gdk, gio, glib, prelude::*};
+use relm4::prelude::*;
+
+use crate::core::{
+    Bookmark, TRASH_URI, bookmarks_file, home_dir, load_bookmarks, save_bookmarks, user_dirs,
+};
+
+/// What a row of the sidebar stands for.
+enum Row {
+    Heading,
+    Place(PathBuf),
+    /// A bookmark with its index in the bookmarks file.
+    Bookmark(usize, PathBuf),
+}
+
+impl Row {
+    fn path(&self) -> Option<&Path> {
+        match self {
+            Row::Heading => None,
+            Row::Place(path) | Row::Bookmark(_, path) => Some(path),
+        }
+    }
+}
+
+/// The places, devices and bookmarks to navigate to.
+pub struct Sidebar {
+    list: gtk::ListBox,
+    rows: Vec<Row>,
+    /// All entries of the bookmarks file, including the remote ones that are not shown.
+    bookmarks: Vec<Bookmark>,
+    usage_bars: Vec<(PathBuf, gtk::LevelBar)>,
+    current_dir: String,
+    volume_monitor: gio::VolumeMonitor,
+    _bookmarks_monitor: Option<gio::FileMonitor>,
+}
+
+fn places() -> Vec<Place> {
+    let mut places = Vec::new();
+    if let Some(home) = home_dir() {
+        places.push(Place::new("Home", "user-home-symbolic", home));
+    }
+    for (dir, path) in user_dirs() {
+        if path.is_dir() {
+            places.push(Place::new(dir.title(), user_dir_icon(dir), path));
+        }
+    }
+    places.push(Place::new("Trash", "user-trash-symbolic", TRASH_URI.into()));
+    places.push(Place::new("Computer", "drive-harddisk-symbolic", "/".into()).with_usage());
+    places
+}
+
+fn devices(volume_monitor: &gio::VolumeMonitor) -> Vec<Place> {
+    volume_monitor
+        .mounts()
+        .into_iter()
+        .filter(|mount| !mount.is_shadowed())
+        .filter_map(|mount| {
+            let path = mount.root().path().filter(|path| path != Path::new("/"))?;
+            Some(Place {
+                name: mount.name().to_string(),
+                icon: mount.symbolic_icon(),
+                path,
+                show_usage: true,
+                missing: false,
+            })
+        })
+        .collect()
+}
+
+/// The used fraction, free and total bytes of the filesystem of each path.
+fn filesystem_usage(paths: Vec<PathBuf>) -> Vec<(PathBuf, f64, u64, u64)> {
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let info = gio::File::for_path(&path)
+                .query_filesystem_info("filesystem::size,filesystem::free", gio::Cancellable::NONE)
+                .ok()?;
+            let size = info.attribute_uint64("filesystem::size");
+            let free = info.attribute_uint64("filesystem::free");
+            (size > 0).then(|| (path, 1.0 - free as f64 / size as f64, free, size))
+        })
+        .collect()
+}
+
+/// Opens the Rename and Remove menu of a bookmark row on a secondary click.
+fn attach_bookmark_menu(row: &gtk::ListBoxRow, index: usize) {
+    let target = (index as u32).to_variant();
+    let menu = gio::Menu::new();
+    let rename = gio::MenuItem::new(Some("Rename…"), None);
+    rename.set_action_and_target_value(Some("sidebar.rename_bookmark"), Some(&target));
+    menu.append_item(&rename);
+    let remove = gio::MenuItem::new(Some("Remove"), None);
+    remove.set_action_and_target_value(Some("sidebar.remove_bookmark"), Some(&target));
+    menu.append_item(&remove);
+
+    let popover = gtk::PopoverMenu::from_model(Some(&menu));
+    popover.set_parent(row);
+    popover.set_has_arrow(false);
+
+    let secondary_click = gtk::GestureClick::new();
+    secondary_click.set_button(gdk::ffi::GDK_BUTTON_SECONDARY as u32);
+    secondary_click.connect_pressed({
+        let popover = popover.clone();
+        move |gesture, _, _, _| {
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+            popover.popup();
+        }
+    });
+    row.add_controller(secondary_click);
+    row.connect_destroy(move |_| popover.unparent());
+}
+
+/// Lets a bookmark row be dragged onto another one to reorder the bookmarks.
+fn attach_bookmark_drag(row: &gtk::ListBoxRow, index: usize) {
+    let content = gdk::ContentProvider::for_value(&(index as u32).to_value());
+    let drag_source = gtk::DragSource::new();
+    drag_source.set_actions(gdk::DragAction::MOVE);
+    drag_source.set_content(Some(&content));
+    row.add_controller(drag_source);
+}
+
+/// Takes bookmarks dragged within the list and folders dragged from the items or
+/// from other applications.
+fn drop_target(list: &gtk::ListBox, sender: &ComponentSender<Sidebar>) -> gtk::DropTarget {
+    let drop_target = gtk::DropTarget::new(
+        glib::Type::INVALID,
+        gdk::DragAction::COPY | gdk::DragAction::MOVE | gdk::DragAction::LINK,
+    );
+    drop_target.set_types(&[u32::static_type(), gdk::FileList::static_type()]);
+    drop_target.connect_drop({
+        let list = list.downgrade();
+        let sender = sender.clone();
+        move |_, value, _, y| {
+            if let Ok(index) = value.get::<u32>() {
+                let target = list
+                    .upgrade()
+                    .and_then(|list| list.row_at_y(y as i32))
+                    .map(|row| row.index());
+                sender.input(SidebarInput::DropBookmark(index as usize, target));
+                return true;
+            }
+
+            let folders: Vec<PathBuf> = value
+                .get::<gdk::FileList>()
+                .map(|files| files.files())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|file| file.path())
+                .filter(|path| path.is_dir())
+                .collect();
+            for folder in &folders {
+                sender.input(SidebarInput::AddBookmark(folder.clone()));
+            }
+            !folders.is_empty()
+        }
+    });
+    drop_target
+}
+
+impl Sidebar {
+    pub fn init(current_dir: &str) -> SidebarInit {
+        SidebarInit::new(current_dir.to_owned())
+    }
+
+    fn rebuild(&mut self, sender: &ComponentSender<Self>) {
+        while let Some(row) = self.list.row_at_index(0) {
+            self.list.remove(&row);
+        }
+        self.rows.clear();
+        self.usage_bars.clear();
+
+        let sections = [
+            (None, places()),
+            (Some("Devices"), devices(&self.volume_monitor)),
+        ];
+        for (title, places) in sections {
+            if places.is_empty() {
+                continue;
+            }
+            if let Some(title) = title {
+                self.append_heading(title);
+            }
+            for place in places {
+                let (row, usage_bar) = place.row();
+                self.list.append(&row);
+                if let Some(usage_bar) = usage_bar {
+                    self.usage_bars.push((place.path.clone(), usage_bar));
+                }
+                self.rows.push(Row::Place(place.path));
+            }
+        }
+
+        let bookmarks: Vec<(usize, Place)> = self
+            .bookmarks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, bookmark)| {
+                let place = Place::new(&bookmark.name(), "folder-symbolic", bookmark.path()?);
+                Some((index, place.checked()))
+            })
+            .collect();
+        if !bookmarks.is_empty() {
+            self.append_heading("Bookmarks");
+        }
+        for (index, place) in bookmarks {
+            let (row, _) = place.row();
+            attach_bookmark_menu(&row, index);
+            attach_bookmark_drag(&row, index);
+            self.list.append(&row);
+            self.rows.push(Row::Bookmark(index, place.path));
+        }
+        self.select_current();
+
+        let paths = self
+            .usage_bars
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect();
+        sender.spawn_oneshot_command(move || SidebarCommand::UsageLoaded(filesystem_usage(paths)));
+    }
+
+    fn append_heading(&mut self, title: &str) {
+        self.list.append(&heading_row(title));
+        self.rows.push(Row::Heading);
+    }
+
+    fn select_current(&self) {
+        let current_dir = Path::new(&self.current_dir);
+        let row = self
+            .rows
+            .iter()
+            .position(|row| row.path() == Some(current_dir))
+            .and_then(|index| self.list.row_at_index(index as i32));
+        self.list.select_row(row.as_ref());
+    }
+
+    fn row(&self, index: i32) -> Option<&Row> {
+        self.rows.get(usize::try_from(index).ok()?)
+    }
+
+    /// Writes the bookmarks back and shows them; the file monitor reloads them again.
+    fn save_bookmarks(&mut self, sender: &ComponentSender<Self>) {
+        if let Err(error) = save_bookmarks(&self.bookmarks) {
+            let _ = sender.output(SidebarOutput::Error(error.to_string()));
+        }
+        self.rebuild(sender);
+    }
+
+    /// Asks for the new label of a bookmark in a popover on its row.
+    fn start_rename(&self, index: usize, sender: &ComponentSender<Self>) {
+        let position = self
+            .rows
+            .iter()
+            .position(|row| matches!(row, Row::Bookmark(bookmark, _) if *bookmark == index));
+        let row = position.and_then(|position| self.list.row_at_index(position as i32));
+        let (Some(row), Some(bookmark)) = (row, self.bookmarks.get(index)) else {
+            return;
+        };
+
+        let entry = gtk::Entry::new();
+        entry.set_text(&bookmark.name());
+        let popover = gtk::Popover::new();
+        popover.set_child(Some(&entry));
+        popover.set_parent(&row);
+        popover.connect_closed(|popover| popover.unparent());
+        entry.connect_activate({
+            let sender = sender.clone();
+            let popover = popover.downgrade();
+            move |entry| {
+                let label = entry.text().trim().to_owned();
+                sender.input(SidebarInput::RenameBookmark(index, label));
+                if let Some(popover) = popover.upgrade() {
+                    popover.popdown();
+                }
+            }
+        });
+        popover.popup();
+    }
+}
+
+#[relm4::component(pub)]
+impl Component for Sidebar {
+    type Init = SidebarInit;
+    type Input = SidebarInput;
+    type Output = SidebarOutput;
+    type CommandOutput = SidebarCommand;
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let volume_monitor = gio::VolumeMonitor::get();
+        volume_monitor.connect_mount_added({
+            let sender = sender.clone();
+            move |_, _| sender.input(SidebarInput::Refresh)
+        });
+        volume_monitor.connect_mount_removed({
+            let sender = sender.clone();
+            move |_, _| sender.input(SidebarInput::Refresh)
+        });
+        volume_monitor.connect_mount_changed({
+            let sender = sender.clone();
+            move |_, _| sender.input(SidebarInput::Refresh)
+        });
+
+        let bookmarks_monitor = bookmarks_file().and_then(|file| {
+            gio::File::for_path(file)
+                .monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE)
+                .ok()
+        });
+        if let Some(monitor) = &bookmarks_monitor {
+            let sender = sender.clone();
+            monitor.connect_changed(move |_, _, _, _| sender.input(SidebarInput::Refresh));
+        }
+
+        let mut model = Self {
+            list: gtk::ListBox::new(),
+            rows: Vec::new(),
+            bookmarks: load_bookmarks(),
+            usage_bars: Vec::new(),
+            current_dir: init.current_dir().to_owned(),
+            volume_monitor,
+            _bookmarks_monitor: bookmarks_monitor,
+        };
+        model.rebuild(&sender);
+
+        let list = &model.list;
+        let widgets = view_output!();
+
+        let bookmark_action = |name: &str, input: fn(usize) -> SidebarInput| {
+            let sender = sender.clone();
+            gio::ActionEntry::builder(name)
+                .parameter_type(Some(glib::VariantTy::UINT32))
+                .activate(
+                    move |_: &gio::SimpleActionGroup, _, parameter: Option<&glib::Variant>| {
+                        if let Some(index) = parameter.and_then(glib::Variant::get::<u32>) {
+                            sender.input(input(index as usize));
+                        }
+                    },
+                )
+                .build()
+        };
+        let actions = gio::SimpleActionGroup::new();
+        actions.add_action_entries([
+            bookmark_action("rename_bookmark", SidebarInput::StartRename),
+            bookmark_action("remove_bookmark", SidebarInput::RemoveBookmark),
+        ]);
+        root.insert_action_group("sidebar", Some(&actions));
+
+        ComponentParts { model, widgets }
+    }
+
+    view! {
+        gtk::ScrolledWindow {
+            set_hscrollbar_policy: gtk::PolicyType::Never,
+            set_width_request: 160,
+
+            #[local_ref]
+            list -> gtk::ListBox {
+                set_css_classes: &["navigation-sidebar"],
+                set_selection_mode: gtk::SelectionMode::Single,
+                add_controller: drop_target(list, &sender),
+                connect_row_activated[sender] => move |_, row| {
+                    sender.input(SidebarInput::Activate(row.index()));
+                },
+            },
+        }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _: &Self::Root) {
+        match message {
+            SidebarInput::SetDirectory(current_dir) => {
+                self.current_dir = current_dir;
+                self.select_current();
+            }
+            SidebarInput::Refresh => {
+                self.bookmarks = load_bookmarks();
+                self.rebuild(&sender);
+            }
+            SidebarInput::Activate(index) => {
+                let path = self
+                    .row(index)
+                    .and_then(Row::path)
+                    .filter(|path| path.exists() || *path == Path::new(TRASH_URI));
+                if let Some(path) = path {
+                    let path = path.display().to_string();
+                    let _ = sender.output(SidebarOutput::Navigate(path));
+                }
+            }
+            SidebarInput::AddBookmark(path) => {
+                let bookmark = Bookmark::new(&path);
+                if !self.bookmarks.iter().any(|known| known.uri == bookmark.uri) {
+                    self.bookmarks.push(bookmark);
+                    self.save_bookmarks(&sender);
+                }
+            }
+            SidebarInput::StartRename(index) => self.start_rename(index, &sender),
+            SidebarInput::RenameBookmark(index, label) => {
+                if let Some(bookmark) = self.bookmarks.get_mut(index) {
+                    bookmark.label = Some(label).filter(|label| !label.is_empty());
+                    self.save_bookmarks(&sender);
+                }
+            }
+            SidebarInput::RemoveBookmark(index) => {
+                if index < self.bookmarks.len() {
+                    self.bookmarks.remove(index);
+                    self.save_bookmarks(&sender);
+                }
+            }
+            SidebarInput::DropBookmark(from, target) => {
+                let to = match target.and_then(|index| self.row(index)) {
+                    Some(Row::Bookmark(to, _)) => *to,
+                    _ => self.bookmarks.len().saturating_sub(1),
+                };
+                if from != to && from < self.bookmarks.len() {
+                    let bookmark = self.bookmarks.remove(from);
+                    self.bookmarks.insert(to, bookmark);
+                    self.save_bookmarks(&sender);
+                }
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        _: ComponentSender<Self>,
+        _: &Self::Root,
+    ) {
+        match message {
+            SidebarCommand::UsageLoaded(usage) => {
+                for (path, used, free, size) in usage {
+                    let bars = self
+                        .usage_bars
+                        .iter()
+                        .filter(|(bar_path, _)| *bar_path == path);
+                    for (_, bar) in bars {
+                        bar.set_value(used);
+                        bar.set_tooltip_text(Some(&format!(
+                            "{} free of {}",
+                            glib::format_size(free),
+                            glib::format_size(size)
+                        )));
+                    }
+                }
+            }
+        }
+    }
+}
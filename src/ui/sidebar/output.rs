@@ -0,0 +1,5 @@
+#[derive(Debug)]
+pub enum SidebarOutput {
+    Navigate(String),
+    Error(String),
+}
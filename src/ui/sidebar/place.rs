@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use relm4::gtk::{self, gio, prelude::*};
+
+use crate::core::UserDir;
+
+/// A location listed in the sidebar.
+pub struct Place {
+    pub name: String,
+    pub icon: gio::Icon,
+    pub path: PathBuf,
+    /// Whether the free space of the filesystem is shown.
+    pub show_usage: bool,
+    /// Whether the place no longer exists, like the target of an old bookmark.
+    pub missing: bool,
+}
+
+impl Place {
+    pub fn new(name: &str, icon_name: &str, path: PathBuf) -> Self {
+        Self {
+            name: name.to_owned(),
+            icon: gio::ThemedIcon::new(icon_name).upcast(),
+            path,
+            show_usage: false,
+            missing: false,
+        }
+    }
+
+    pub fn with_usage(mut self) -> Self {
+        self.show_usage = true;
+        self
+    }
+
+    /// Flags the place as missing when its path does not exist.
+    pub fn checked(mut self) -> Self {
+        if !self.path.exists() {
+            self.missing = true;
+            self.icon = gio::ThemedIcon::new("dialog-warning-symbolic").upcast();
+        }
+        self
+    }
+
+    /// The row of the place, with the usage bar if it has one.
+    pub fn row(&self) -> (gtk::ListBoxRow, Option<gtk::LevelBar>) {
+        let content = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        content.append(&gtk::Image::from_gicon(&self.icon));
+        let label = gtk::Label::new(Some(&self.name));
+        label.set_xalign(0.0);
+        label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        label.set_hexpand(true);
+        content.append(&label);
+
+        let row_box = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        row_box.append(&content);
+        let usage = self.show_usage.then(|| {
+            let bar = gtk::LevelBar::for_interval(0.0, 1.0);
+            bar.add_css_class("usage-bar");
+            row_box.append(&bar);
+            bar
+        });
+
+        let row = gtk::ListBoxRow::new();
+        row.set_child(Some(&row_box));
+        if self.missing {
+            row.add_css_class("missing-place");
+            let tooltip = format!("{} no longer exists", self.path.display());
+            row.set_tooltip_text(Some(&tooltip));
+        } else {
+            row.set_tooltip_text(Some(&self.path.display().to_string()));
+        }
+        (row, usage)
+    }
+}
+
+/// A non-selectable row naming the places below it.
+pub fn heading_row(title: &str) -> gtk::ListBoxRow {
+    let label = gtk::Label::new(Some(title));
+    label.set_xalign(0.0);
+    label.set_css_classes(&["sidebar-heading", "dim-label"]);
+
+    let row = gtk::ListBoxRow::new();
+    row.set_child(Some(&label));
+    row.set_activatable(false);
+    row.set_selectable(false);
+    row
+}
+
+pub fn user_dir_icon(dir: UserDir) -> &'static str {
+    match dir {
+        UserDir::Desktop => "user-desktop-symbolic",
+        UserDir::Documents => "folder-documents-symbolic",
+        UserDir::Downloads => "folder-download-symbolic",
+        UserDir::Music => "folder-music-symbolic",
+        UserDir::Pictures => "folder-pictures-symbolic",
+        UserDir::Videos => "folder-videos-symbolic",
+        UserDir::Templates => "folder-templates-symbolic",
+    }
+}
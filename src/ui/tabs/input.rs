@@ -0,0 +1,20 @@
+use relm4::adw;
+
+use crate::ui::items_box::{Column, ItemsBoxInput, ItemsBoxOutput, Zoom};
+
+#[derive(Debug)]
+pub enum TabsInput {
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    /// Also sent to have the state of the selected tab reported again.
+    TabSelected,
+    TabClosed(adw::TabPage),
+    /// An output of the items box of the tab with the given id.
+    TabOutput(usize, ItemsBoxOutput),
+    /// A message for the items box of the selected tab.
+    Current(ItemsBoxInput),
+    SetColumnVisible(Column, bool),
+    SetZoom(Zoom),
+}
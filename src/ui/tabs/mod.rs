@@ -0,0 +1,290 @@
+pub mod init;
+mod input;
+mod output;
+
+use std::path::Path;
+
+use init::TabsInit;
+pub(crate) use input::TabsInput;
+pub(crate) use output::TabsOutput;
+use relm4::adw::{self, prelude::*};
+use relm4::gtk::glib;
+use relm4::prelude::*;
+
+use crate::ui::items_box::{
+    Column, ItemsBox, ItemsBoxInput, ItemsBoxOutput, Sorting, ViewMode, Zoom,
+};
+
+/// A tab with its items box and the state the window shows while it is selected.
+struct Tab {
+    id: usize,
+    page: adw::TabPage,
+    items_box: Controller<ItemsBox>,
+    current_dir: String,
+    history: (Vec<String>, Vec<String>),
+    show_hidden_items: bool,
+    view_mode: ViewMode,
+    sorting: Sorting,
+}
+
+impl Tab {
+    fn report(&self, sender: &ComponentSender<Tabs>) {
+        let (back, forward) = self.history.clone();
+        let outputs = [
+            TabsOutput::DirectoryLoaded(self.current_dir.clone()),
+            TabsOutput::HistoryChanged(back, forward),
+            TabsOutput::HiddenItemsChanged(self.show_hidden_items),
+            TabsOutput::ViewModeChanged(self.view_mode),
+            TabsOutput::SortingChanged(self.sorting),
+        ];
+        for output in outputs {
+            let _ = sender.output(output);
+        }
+    }
+}
+
+fn set_page_location(page: &adw::TabPage, current_dir: &str) {
+    let title = Path::new(current_dir)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| current_dir.to_owned());
+    page.set_title(&title);
+    page.set_tooltip(current_dir);
+}
+
+pub struct Tabs {
+    view: adw::TabView,
+    tabs: Vec<Tab>,
+    next_id: usize,
+    hidden_columns: Vec<Column>,
+    zoom: Zoom,
+}
+
+impl Tabs {
+    pub fn init(current_dir: &str, show_hidden_items: bool) -> TabsInit {
+        TabsInit::new(current_dir.to_owned(), show_hidden_items)
+    }
+
+    /// Opens a tab next to the selected one without selecting it.
+    fn open_tab(
+        &mut self,
+        current_dir: &str,
+        show_hidden_items: bool,
+        sender: &ComponentSender<Self>,
+    ) -> adw::TabPage {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let items_box = ItemsBox::builder()
+            .launch(ItemsBox::init(current_dir, show_hidden_items))
+            .forward(sender.input_sender(), move |output| {
+                TabsInput::TabOutput(id, output)
+            });
+        for column in &self.hidden_columns {
+            items_box.emit(ItemsBoxInput::SetColumnVisible(*column, false));
+        }
+        items_box.emit(ItemsBoxInput::SetZoom(self.zoom));
+
+        let page = self
+            .view
+            .add_page(items_box.widget(), self.view.selected_page().as_ref());
+        set_page_location(&page, current_dir);
+        self.tabs.push(Tab {
+            id,
+            page: page.clone(),
+            items_box,
+            current_dir: current_dir.to_owned(),
+            history: Default::default(),
+            show_hidden_items,
+            view_mode: ViewMode::List,
+            sorting: Sorting::default(),
+        });
+        page
+    }
+
+    fn selected(&self) -> Option<&Tab> {
+        let page = self.view.selected_page()?;
+        self.tabs.iter().find(|tab| tab.page == page)
+    }
+
+    fn selected_mut(&mut self) -> Option<&mut Tab> {
+        let page = self.view.selected_page()?;
+        self.tabs.iter_mut().find(|tab| tab.page == page)
+    }
+
+    /// Selects the tab `offset` positions away, wrapping around at the ends.
+    fn cycle(&self, offset: i32) {
+        let n_pages = self.view.n_pages();
+        if let Some(page) = self.view.selected_page().filter(|_| n_pages > 1) {
+            let position = (self.view.page_position(&page) + offset).rem_euclid(n_pages);
+            self.view.set_selected_page(&self.view.nth_page(position));
+        }
+    }
+
+    fn handle_tab_output(
+        &mut self,
+        id: usize,
+        output: ItemsBoxOutput,
+        sender: &ComponentSender<Self>,
+    ) {
+        let selected = self.selected().is_some_and(|tab| tab.id == id);
+        let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == id) else {
+            return;
+        };
+
+        let output = match output {
+            ItemsBoxOutput::DirectoryLoaded(current_dir) => {
+                set_page_location(&tab.page, &current_dir);
+                tab.current_dir = current_dir.clone();
+                TabsOutput::DirectoryLoaded(current_dir)
+            }
+            ItemsBoxOutput::HistoryChanged(back, forward) => {
+                tab.history = (back.clone(), forward.clone());
+                TabsOutput::HistoryChanged(back, forward)
+            }
+            ItemsBoxOutput::SortingChanged(sorting) => {
+                tab.sorting = sorting;
+                TabsOutput::SortingChanged(sorting)
+            }
+            ItemsBoxOutput::OpenInNewTab(path) => {
+                let show_hidden_items = tab.show_hidden_items;
+                self.open_tab(&path, show_hidden_items, sender);
+                return;
+            }
+            ItemsBoxOutput::Error(error) => {
+                let _ = sender.output(TabsOutput::Error(error));
+                return;
+            }
+            ItemsBoxOutput::StartJob(operation) => {
+                let _ = sender.output(TabsOutput::StartJob(operation));
+                return;
+            }
+            ItemsBoxOutput::ZoomIn => TabsOutput::ZoomIn,
+            ItemsBoxOutput::ZoomOut => TabsOutput::ZoomOut,
+        };
+        if selected {
+            let _ = sender.output(output);
+        }
+    }
+}
+
+#[relm4::component(pub)]
+impl Component for Tabs {
+    type Init = TabsInit;
+    type Input = TabsInput;
+    type Output = TabsOutput;
+    type CommandOutput = ();
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let mut model = Self {
+            view: adw::TabView::new(),
+            tabs: Vec::new(),
+            next_id: 0,
+            hidden_columns: Vec::new(),
+            zoom: Zoom::default(),
+        };
+        model.open_tab(init.current_dir(), init.show_hidden_items(), &sender);
+
+        let tab_view = &model.view;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+            set_vexpand: true,
+
+            adw::TabBar {
+                set_view: Some(tab_view),
+            },
+
+            #[local_ref]
+            tab_view -> adw::TabView {
+                set_vexpand: true,
+                // Ctrl+Tab is an app shortcut, see `shortcuts.rs`.
+                remove_shortcuts: adw::TabViewShortcuts::CONTROL_TAB
+                    | adw::TabViewShortcuts::CONTROL_SHIFT_TAB,
+                connect_selected_page_notify => TabsInput::TabSelected,
+                connect_close_page[sender] => move |_, page| {
+                    sender.input(TabsInput::TabClosed(page.clone()));
+                    glib::Propagation::Proceed
+                },
+            },
+        }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _: &Self::Root) {
+        match message {
+            TabsInput::NewTab => {
+                let Some(tab) = self.selected() else {
+                    return;
+                };
+                let (current_dir, show_hidden_items) =
+                    (tab.current_dir.clone(), tab.show_hidden_items);
+                let page = self.open_tab(&current_dir, show_hidden_items, &sender);
+                self.view.set_selected_page(&page);
+            }
+            TabsInput::CloseTab => {
+                if let Some(page) = self.view.selected_page() {
+                    self.view.close_page(&page);
+                }
+            }
+            TabsInput::NextTab => self.cycle(1),
+            TabsInput::PreviousTab => self.cycle(-1),
+            TabsInput::TabSelected => {
+                if let Some(tab) = self.selected() {
+                    tab.report(&sender);
+                }
+            }
+            TabsInput::TabClosed(page) => {
+                self.tabs.retain(|tab| tab.page != page);
+                if self.tabs.is_empty() {
+                    let _ = sender.output(TabsOutput::Closed);
+                }
+            }
+            TabsInput::TabOutput(id, output) => self.handle_tab_output(id, output, &sender),
+            TabsInput::Current(message) => {
+                let Some(tab) = self.selected_mut() else {
+                    return;
+                };
+                let message = match message {
+                    ItemsBoxInput::ToggleShowHiddenItems => {
+                        ItemsBoxInput::ShowHiddenItems(!tab.show_hidden_items)
+                    }
+                    message => message,
+                };
+                match &message {
+                    ItemsBoxInput::ShowHiddenItems(show) if *show != tab.show_hidden_items => {
+                        tab.show_hidden_items = *show;
+                        let _ = sender.output(TabsOutput::HiddenItemsChanged(*show));
+                    }
+                    ItemsBoxInput::SetViewMode(view_mode) => tab.view_mode = *view_mode,
+                    _ => {}
+                }
+                tab.items_box.emit(message);
+            }
+            TabsInput::SetColumnVisible(column, visible) => {
+                self.hidden_columns.retain(|hidden| *hidden != column);
+                if !visible {
+                    self.hidden_columns.push(column);
+                }
+                for tab in &self.tabs {
+                    tab.items_box
+                        .emit(ItemsBoxInput::SetColumnVisible(column, visible));
+                }
+            }
+            TabsInput::SetZoom(zoom) => {
+                self.zoom = zoom;
+                for tab in &self.tabs {
+                    tab.items_box.emit(ItemsBoxInput::SetZoom(zoom));
+                }
+            }
+        }
+    }
+}
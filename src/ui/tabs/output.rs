@@ -0,0 +1,18 @@
+use crate::core::Operation;
+use crate::ui::items_box::{Sorting, ViewMode};
+
+/// The state of the selected tab; all of it is sent again when another tab is selected.
+#[derive(Debug)]
+pub enum TabsOutput {
+    DirectoryLoaded(String),
+    HistoryChanged(Vec<String>, Vec<String>),
+    HiddenItemsChanged(bool),
+    ViewModeChanged(ViewMode),
+    SortingChanged(Sorting),
+    Error(String),
+    StartJob(Operation),
+    ZoomIn,
+    ZoomOut,
+    /// The last tab was closed.
+    Closed,
+}
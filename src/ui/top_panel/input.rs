@@ -1,6 +1,11 @@
+use crate::core::JobEvent;
+
 #[derive(Debug)]
 pub enum TopPanelInput {
-    DirectoryLoaded(bool),
+    DirectoryLoaded(String),
+    HistoryChanged(Vec<String>, Vec<String>),
+    EditLocation,
     ShowHiddenItems(bool),
-    ToggleShowHiddenItems,
+    SetShowHiddenItems(bool),
+    Job(JobEvent),
 }
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use relm4::ComponentSender;
+use relm4::gtk::{self, glib, prelude::*};
+
+use super::{TopPanel, TopPanelOutput};
+use crate::core::{JobId, JobProgress};
+
+const WAITING: &str = "Waiting for other operations…";
+
+struct JobRow {
+    description: String,
+    widget: gtk::Box,
+    progress: gtk::ProgressBar,
+    status: gtk::Label,
+    pause: gtk::ToggleButton,
+    /// The last progress, shown again when the job is resumed.
+    last: Option<JobProgress>,
+}
+
+/// The running and waiting jobs shown in the progress popover.
+pub struct JobList {
+    widget: gtk::Box,
+    rows: HashMap<JobId, JobRow>,
+}
+
+impl JobList {
+    pub fn new() -> Self {
+        let widget = gtk::Box::new(gtk::Orientation::Vertical, 12);
+        widget.set_width_request(360);
+        widget.add_css_class("job-list");
+        Self {
+            widget,
+            rows: HashMap::new(),
+        }
+    }
+
+    pub fn widget(&self) -> &gtk::Box {
+        &self.widget
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn add(&mut self, id: JobId, description: &str, sender: &ComponentSender<TopPanel>) {
+        let title = gtk::Label::new(Some(description));
+        title.set_xalign(0.0);
+        title.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+        title.set_hexpand(true);
+
+        let pause = gtk::ToggleButton::new();
+        pause.set_icon_name("media-playback-pause-symbolic");
+        pause.set_tooltip_text(Some("Pause"));
+        pause.add_css_class("flat");
+        pause.connect_toggled({
+            let sender = sender.clone();
+            move |button| {
+                let _ = sender.output(TopPanelOutput::PauseJob(id, button.is_active()));
+            }
+        });
+
+        let cancel = gtk::Button::from_icon_name("process-stop-symbolic");
+        cancel.set_tooltip_text(Some("Cancel"));
+        cancel.add_css_class("flat");
+        cancel.connect_clicked({
+            let sender = sender.clone();
+            move |_| {
+                let _ = sender.output(TopPanelOutput::CancelJob(id));
+            }
+        });
+
+        let header = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        header.append(&title);
+        header.append(&pause);
+        header.append(&cancel);
+
+        let progress = gtk::ProgressBar::new();
+        let status = gtk::Label::new(Some(WAITING));
+        status.set_xalign(0.0);
+        status.set_css_classes(&["dim-label", "caption"]);
+
+        let widget = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        widget.append(&header);
+        widget.append(&progress);
+        widget.append(&status);
+        self.widget.append(&widget);
+
+        let row = JobRow {
+            description: description.to_owned(),
+            widget,
+            progress,
+            status,
+            pause,
+            last: None,
+        };
+        self.rows.insert(id, row);
+    }
+
+    pub fn set_started(&self, id: JobId) {
+        if let Some(row) = self.rows.get(&id) {
+            row.status.set_text("Preparing…");
+        }
+    }
+
+    pub fn set_progress(&mut self, id: JobId, progress: JobProgress) {
+        if let Some(row) = self.rows.get_mut(&id) {
+            row.progress.set_fraction(progress.fraction());
+            row.status.set_text(&progress_text(&progress));
+            row.last = Some(progress);
+        }
+    }
+
+    pub fn set_paused(&self, id: JobId, paused: bool) {
+        let Some(row) = self.rows.get(&id) else {
+            return;
+        };
+        let (icon, tooltip) = match paused {
+            true => ("media-playback-start-symbolic", "Resume"),
+            false => ("media-playback-pause-symbolic", "Pause"),
+        };
+        row.pause.set_icon_name(icon);
+        row.pause.set_tooltip_text(Some(tooltip));
+        match (paused, &row.last) {
+            (true, _) => row.status.set_text("Paused"),
+            (false, Some(progress)) => row.status.set_text(&progress_text(progress)),
+            (false, None) => row.status.set_text(WAITING),
+        }
+    }
+
+    /// Removes the row of a finished job and returns its description.
+    pub fn remove(&mut self, id: JobId) -> Option<String> {
+        let row = self.rows.remove(&id)?;
+        self.widget.remove(&row.widget);
+        Some(row.description)
+    }
+
+    /// The progress of all jobs together.
+    pub fn fraction(&self) -> f64 {
+        let (done, total) = self
+            .rows
+            .values()
+            .filter_map(|row| row.last)
+            .fold((0, 0), |(done, total), progress| {
+                (done + progress.done_bytes, total + progress.total_bytes)
+            });
+        match total {
+            0 => 0.0,
+            total => done as f64 / total as f64,
+        }
+    }
+}
+
+/// Like "12 MB of 40 MB, 3 of 10 files — 5 MB/s, 6 s left".
+fn progress_text(progress: &JobProgress) -> String {
+    let mut text = format!(
+        "{} of {}, {} of {} files",
+        glib::format_size(progress.done_bytes),
+        glib::format_size(progress.total_bytes),
+        progress.done_files,
+        progress.total_files,
+    );
+    if let Some(remaining) = progress.remaining() {
+        let throughput = glib::format_size(progress.throughput as u64);
+        text.push_str(&format!(
+            " — {throughput}/s, {} left",
+            format_remaining(remaining)
+        ));
+    }
+    text
+}
+
+fn format_remaining(remaining: Duration) -> String {
+    match remaining.as_secs() {
+        seconds @ 0..60 => format!("{seconds} s"),
+        seconds @ 60..3600 => format!("{} min", seconds.div_ceil(60)),
+        seconds => format!("{} h {} min", seconds / 3600, seconds % 3600 / 60),
+    }
+}
@@ -1,22 +1,77 @@
 pub mod init;
 mod input;
+mod jobs;
 mod output;
 
 use init::TopPanelInit;
 pub(crate) use input::TopPanelInput;
+use jobs::JobList;
 pub(crate) use output::TopPanelOutput;
-use relm4::gtk::prelude::*;
+use relm4::gtk::{gio, prelude::*};
 use relm4::prelude::*;
+use std::path::Path;
+
+use crate::core::JobEvent;
+use crate::ui::path_bar::{PathBar, PathBarInput, PathBarOutput};
 
 #[tracker::track]
 pub struct TopPanel {
     has_parent_dir: bool,
     show_hidden_items: bool,
+    can_go_back: bool,
+    can_go_forward: bool,
+    has_jobs: bool,
+    jobs_fraction: f64,
+    #[tracker::do_not_track]
+    jobs: JobList,
+    #[tracker::do_not_track]
+    back_menu: gio::Menu,
+    #[tracker::do_not_track]
+    forward_menu: gio::Menu,
+    #[tracker::do_not_track]
+    path_bar: Controller<PathBar>,
+}
+
+/// Fills a history dropdown; each entry activates `action` with the number of steps.
+fn fill_history_menu(menu: &gio::Menu, action: &str, locations: &[String]) {
+    menu.remove_all();
+    for (index, location) in locations.iter().enumerate() {
+        let item = gio::MenuItem::new(Some(location), None);
+        item.set_action_and_target_value(Some(action), Some(&(index as u32 + 1).to_variant()));
+        menu.append_item(&item);
+    }
+}
+
+/// Opens the history dropdown of a button on a secondary click or a long press.
+fn attach_history_menu(button: &gtk::Button, menu: &gio::Menu) {
+    let popover = gtk::PopoverMenu::from_model(Some(menu));
+    popover.set_parent(button);
+    popover.set_has_arrow(false);
+
+    let secondary_click = gtk::GestureClick::new();
+    secondary_click.set_button(gtk::gdk::ffi::GDK_BUTTON_SECONDARY as u32);
+    secondary_click.connect_pressed({
+        let popover = popover.clone();
+        move |_, _, _, _| popover.popup()
+    });
+    button.add_controller(secondary_click);
+
+    let long_press = gtk::GestureLongPress::new();
+    long_press.connect_pressed({
+        let popover = popover.clone();
+        move |gesture, _, _| {
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+            popover.popup();
+        }
+    });
+    button.add_controller(long_press);
+
+    button.connect_destroy(move |_| popover.unparent());
 }
 
 impl TopPanel {
-    pub fn init(has_parent_dir: bool, show_hidden_items: bool) -> TopPanelInit {
-        TopPanelInit::new(has_parent_dir, show_hidden_items)
+    pub fn init(current_dir: &str, show_hidden_items: bool) -> TopPanelInit {
+        TopPanelInit::new(current_dir.to_owned(), show_hidden_items)
     }
 }
 
@@ -32,12 +87,26 @@ impl SimpleComponent for TopPanel {
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
         let model = Self {
-            has_parent_dir: init.has_parent_dir(),
+            has_parent_dir: Path::new(init.current_dir()).parent().is_some(),
             show_hidden_items: init.show_hidden_items(),
+            can_go_back: false,
+            can_go_forward: false,
+            has_jobs: false,
+            jobs_fraction: 0.0,
+            jobs: JobList::new(),
+            back_menu: gio::Menu::new(),
+            forward_menu: gio::Menu::new(),
+            path_bar: PathBar::builder()
+                .launch(PathBar::init(init.current_dir()))
+                .forward(sender.output_sender(), |output| match output {
+                    PathBarOutput::Navigate(path) => TopPanelOutput::Navigate(path),
+                }),
             tracker: 0,
         };
 
         let widgets = view_output!();
+        attach_history_menu(&widgets.back_button, &model.back_menu);
+        attach_history_menu(&widgets.forward_button, &model.forward_menu);
 
         ComponentParts { model, widgets }
     }
@@ -47,12 +116,34 @@ impl SimpleComponent for TopPanel {
             set_orientation: gtk::Orientation::Horizontal,
             set_spacing: 5,
 
+            #[name = "back_button"]
             gtk::Button {
                 set_icon_name: "left",
                 set_tooltip_text: Some("Back"),
                 connect_clicked[sender] => move |_| {
                     let _ = sender.output(Self::Output::Back);
                 },
+                #[track = "model.changed(TopPanel::can_go_back())"]
+                set_sensitive: model.can_go_back,
+            },
+
+            #[name = "forward_button"]
+            gtk::Button {
+                set_icon_name: "go-next-symbolic",
+                set_tooltip_text: Some("Forward"),
+                connect_clicked[sender] => move |_| {
+                    let _ = sender.output(Self::Output::Forward);
+                },
+                #[track = "model.changed(TopPanel::can_go_forward())"]
+                set_sensitive: model.can_go_forward,
+            },
+
+            gtk::Button {
+                set_icon_name: "go-up-symbolic",
+                set_tooltip_text: Some("Up"),
+                connect_clicked[sender] => move |_| {
+                    let _ = sender.output(Self::Output::Up);
+                },
                 #[track = "model.changed(TopPanel::has_parent_dir())"]
                 set_sensitive: model.has_parent_dir,
             },
@@ -65,6 +156,31 @@ impl SimpleComponent for TopPanel {
                 },
             },
 
+            model.path_bar.widget(),
+
+            gtk::MenuButton {
+                set_tooltip_text: Some("File Operations"),
+                #[track = "model.changed(TopPanel::has_jobs())"]
+                set_visible: model.has_jobs,
+                #[wrap(Some)]
+                set_child = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 2,
+                    gtk::Image {
+                        set_icon_name: Some("folder-download-symbolic"),
+                    },
+                    gtk::ProgressBar {
+                        add_css_class: "jobs-progress",
+                        #[track = "model.changed(TopPanel::jobs_fraction())"]
+                        set_fraction: model.jobs_fraction,
+                    },
+                },
+                #[wrap(Some)]
+                set_popover = &gtk::Popover {
+                    set_child: Some(model.jobs.widget()),
+                },
+            },
+
             gtk::Box {
                 set_orientation: gtk::Orientation::Vertical,
                 gtk::CheckButton {
@@ -87,16 +203,41 @@ impl SimpleComponent for TopPanel {
     fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
         self.reset();
         match message {
-            Self::Input::DirectoryLoaded(has_parent_dir) => self.set_has_parent_dir(has_parent_dir),
-            Self::Input::ToggleShowHiddenItems => {
-                self.set_show_hidden_items(!self.show_hidden_items);
+            Self::Input::DirectoryLoaded(current_dir) => {
+                self.set_has_parent_dir(Path::new(&current_dir).parent().is_some());
+                self.path_bar.emit(PathBarInput::SetDirectory(current_dir));
             }
+            Self::Input::HistoryChanged(back, forward) => {
+                self.set_can_go_back(!back.is_empty());
+                self.set_can_go_forward(!forward.is_empty());
+                fill_history_menu(&self.back_menu, "win.back", &back);
+                fill_history_menu(&self.forward_menu, "win.forward", &forward);
+            }
+            Self::Input::EditLocation => self.path_bar.emit(PathBarInput::StartEditing),
+            Self::Input::SetShowHiddenItems(show) => self.set_show_hidden_items(show),
             Self::Input::ShowHiddenItems(show) => {
                 self.set_show_hidden_items(show);
                 if self.changed(TopPanel::show_hidden_items()) {
                     let _ = sender.output(Self::Output::HiddenItemsToggled(show));
                 }
             }
+            Self::Input::Job(event) => {
+                match event {
+                    JobEvent::Queued(id, description) => self.jobs.add(id, &description, &sender),
+                    JobEvent::Started(id) => self.jobs.set_started(id),
+                    JobEvent::Progress(id, progress) => self.jobs.set_progress(id, progress),
+                    JobEvent::Paused(id, paused) => self.jobs.set_paused(id, paused),
+                    JobEvent::Finished(id, report) => {
+                        let description = self.jobs.remove(id).unwrap_or_default();
+                        if !report.errors.is_empty() {
+                            let errors = report.errors;
+                            let _ = sender.output(Self::Output::JobFailed(description, errors));
+                        }
+                    }
+                }
+                self.set_has_jobs(!self.jobs.is_empty());
+                self.set_jobs_fraction(self.jobs.fraction());
+            }
         }
     }
 }
@@ -1,6 +1,15 @@
+use crate::core::JobId;
+
 #[derive(Debug)]
 pub enum TopPanelOutput {
     HiddenItemsToggled(bool),
+    Navigate(String),
     Back,
+    Forward,
+    Up,
     Home,
+    PauseJob(JobId, bool),
+    CancelJob(JobId),
+    /// A job finished with the errors of the items it went on past.
+    JobFailed(String, Vec<String>),
 }
@@ -0,0 +1,11 @@
+use relm4::gtk::glib;
+
+use crate::core::TrashedItem;
+
+#[derive(Debug)]
+pub enum TrashCommand {
+    /// The trashed items, newest first, with their content types.
+    Loaded(Vec<(TrashedItem, glib::GString)>),
+    /// The action on the trash described finished with the given errors.
+    Done(&'static str, Vec<String>),
+}
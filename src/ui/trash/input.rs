@@ -0,0 +1,12 @@
+#[derive(Debug)]
+pub enum TrashInput {
+    /// Re-reads the trash directories.
+    Refresh,
+    SelectionChanged,
+    Restore,
+    DeletePermanently,
+    EmptyTrash,
+    /// Deletes the selected items once the user confirmed it.
+    ConfirmDelete,
+    ConfirmEmpty,
+}
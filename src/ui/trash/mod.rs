@@ -0,0 +1,284 @@
+mod command;
+mod input;
+
+use command::TrashCommand;
+pub(crate) use input::TrashInput;
+use relm4::gtk::{gio, glib, prelude::*};
+use relm4::prelude::*;
+
+use crate::core::{
+    LocalTime, TrashedItem, delete_trashed, empty_trash, home_trash, restore, trashed_items,
+};
+use crate::ui::dialogs::{confirm, report_errors};
+
+/// The trashed items, newest first, with the content types of their original names.
+fn load_items() -> Vec<(TrashedItem, glib::GString)> {
+    let mut items: Vec<_> = trashed_items()
+        .into_iter()
+        .map(|item| {
+            let content_type = match item.path.is_dir() {
+                true => "inode/directory".into(),
+                false => gio::content_type_guess(Some(item.name()), None::<&[u8]>).0,
+            };
+            (item, content_type)
+        })
+        .collect();
+    items.sort_by(|(a, _), (b, _)| b.deleted.cmp(&a.deleted));
+    items
+}
+
+fn format_date(time: LocalTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        time.year, time.month, time.day, time.hour, time.minute
+    )
+}
+
+fn item_row(item: &TrashedItem, content_type: &str) -> gtk::ListBoxRow {
+    let icon = gtk::Image::from_gicon(&gio::content_type_get_symbolic_icon(content_type));
+    icon.add_css_class("item-icon");
+
+    let name = gtk::Label::new(Some(&item.name()));
+    name.set_xalign(0.0);
+    name.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+    let location = item
+        .original_path
+        .parent()
+        .map(|parent| parent.display().to_string())
+        .unwrap_or_default();
+    let location = gtk::Label::new(Some(&location));
+    location.set_xalign(0.0);
+    location.set_ellipsize(gtk::pango::EllipsizeMode::Start);
+    location.set_css_classes(&["dim-label", "caption"]);
+    let text = gtk::Box::new(gtk::Orientation::Vertical, 2);
+    text.set_hexpand(true);
+    text.append(&name);
+    text.append(&location);
+
+    let deleted = item.deleted.map(format_date).unwrap_or_default();
+    let deleted = gtk::Label::new(Some(&deleted));
+    deleted.add_css_class("dim-label");
+
+    let content = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    content.add_css_class("item-box");
+    content.append(&icon);
+    content.append(&text);
+    content.append(&deleted);
+    let row = gtk::ListBoxRow::new();
+    row.set_child(Some(&content));
+    row
+}
+
+/// The items in the trash, with the actions to restore or delete them.
+#[tracker::track]
+pub struct Trash {
+    #[tracker::do_not_track]
+    list: gtk::ListBox,
+    #[tracker::do_not_track]
+    items: Vec<TrashedItem>,
+    has_items: bool,
+    has_selection: bool,
+    #[tracker::do_not_track]
+    _monitor: Option<gio::FileMonitor>,
+}
+
+impl Trash {
+    fn selected_items(&self) -> Vec<TrashedItem> {
+        self.list
+            .selected_rows()
+            .iter()
+            .filter_map(|row| self.items.get(row.index() as usize))
+            .cloned()
+            .collect()
+    }
+
+    /// Runs `action` on the selected items off the main thread.
+    fn for_selected(
+        &self,
+        sender: &ComponentSender<Self>,
+        description: &'static str,
+        action: fn(&TrashedItem) -> std::io::Result<()>,
+    ) {
+        let items = self.selected_items();
+        sender.spawn_oneshot_command(move || {
+            let errors = items
+                .iter()
+                .filter_map(|item| action(item).err().map(|error| (item, error)))
+                .map(|(item, error)| format!("{}: {error}", item.name()))
+                .collect();
+            TrashCommand::Done(description, errors)
+        });
+    }
+}
+
+#[relm4::component(pub)]
+impl Component for Trash {
+    type Init = ();
+    type Input = TrashInput;
+    type Output = ();
+    type CommandOutput = TrashCommand;
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Horizontal,
+                set_spacing: 6,
+                set_css_classes: &["trash-header"],
+
+                gtk::Label {
+                    set_label: "Trash",
+                    set_hexpand: true,
+                    set_xalign: 0.0,
+                    set_css_classes: &["title-4"],
+                },
+                gtk::Button {
+                    set_label: "Restore",
+                    #[track = "model.changed(Trash::has_selection())"]
+                    set_sensitive: model.has_selection,
+                    connect_clicked[sender] => move |_| {
+                        sender.input(TrashInput::Restore);
+                    },
+                },
+                gtk::Button {
+                    set_label: "Delete Permanently",
+                    #[track = "model.changed(Trash::has_selection())"]
+                    set_sensitive: model.has_selection,
+                    connect_clicked[sender] => move |_| {
+                        sender.input(TrashInput::DeletePermanently);
+                    },
+                },
+                gtk::Button {
+                    set_label: "Empty Trash",
+                    set_css_classes: &["destructive-action"],
+                    #[track = "model.changed(Trash::has_items())"]
+                    set_sensitive: model.has_items,
+                    connect_clicked[sender] => move |_| {
+                        sender.input(TrashInput::EmptyTrash);
+                    },
+                },
+            },
+            gtk::ScrolledWindow {
+                set_vexpand: true,
+                #[track = "model.changed(Trash::has_items())"]
+                set_visible: model.has_items,
+
+                #[local_ref]
+                list -> gtk::ListBox {
+                    set_css_classes: &["items-list"],
+                    set_selection_mode: gtk::SelectionMode::Multiple,
+                    connect_selected_rows_changed[sender] => move |_| {
+                        sender.input(TrashInput::SelectionChanged);
+                    },
+                },
+            },
+            gtk::Label {
+                set_label: "Trash is empty",
+                set_vexpand: true,
+                set_css_classes: &["dim-label", "title-2"],
+                #[track = "model.changed(Trash::has_items())"]
+                set_visible: !model.has_items,
+            },
+        }
+    }
+
+    fn init(
+        _: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        // Items trashed from other volumes are only seen on the next refresh.
+        let monitor = home_trash().and_then(|trash| {
+            gio::File::for_path(trash.join("info"))
+                .monitor_directory(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE)
+                .ok()
+        });
+        if let Some(monitor) = &monitor {
+            let sender = sender.clone();
+            monitor.connect_changed(move |_, _, _, _| sender.input(TrashInput::Refresh));
+        }
+
+        let model = Self {
+            list: gtk::ListBox::new(),
+            items: Vec::new(),
+            has_items: false,
+            has_selection: false,
+            _monitor: monitor,
+            tracker: 0,
+        };
+        let list = &model.list;
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        self.reset();
+        match message {
+            TrashInput::Refresh => {
+                sender.spawn_oneshot_command(|| TrashCommand::Loaded(load_items()));
+            }
+            TrashInput::SelectionChanged => {
+                self.set_has_selection(!self.list.selected_rows().is_empty());
+            }
+            TrashInput::Restore => self.for_selected(&sender, "Restoring", restore),
+            TrashInput::DeletePermanently => {
+                let message = match &self.selected_items()[..] {
+                    [] => return,
+                    [item] => format!("Permanently delete “{}”?", item.name()),
+                    items => format!("Permanently delete {} items?", items.len()),
+                };
+                let detail = "Deleted items cannot be restored.";
+                confirm(root, &message, detail, "Delete", move || {
+                    sender.input(TrashInput::ConfirmDelete);
+                });
+            }
+            TrashInput::ConfirmDelete => {
+                self.for_selected(&sender, "Deleting", delete_trashed);
+            }
+            TrashInput::EmptyTrash => {
+                let detail = "All items in the Trash will be permanently deleted.";
+                confirm(root, "Empty the Trash?", detail, "Empty Trash", move || {
+                    sender.input(TrashInput::ConfirmEmpty);
+                });
+            }
+            TrashInput::ConfirmEmpty => {
+                sender.spawn_oneshot_command(|| {
+                    TrashCommand::Done("Emptying the Trash", empty_trash())
+                });
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        self.reset();
+        match message {
+            TrashCommand::Loaded(items) => {
+                while let Some(row) = self.list.row_at_index(0) {
+                    self.list.remove(&row);
+                }
+                for (item, content_type) in &items {
+                    self.list.append(&item_row(item, content_type));
+                }
+                self.items = items.into_iter().map(|(item, _)| item).collect();
+                self.set_has_items(!self.items.is_empty());
+                self.set_has_selection(false);
+            }
+            TrashCommand::Done(description, errors) => {
+                if !errors.is_empty() {
+                    let message = match errors.len() {
+                        1 => format!("{description} finished with an error"),
+                        count => format!("{description} finished with {count} errors"),
+                    };
+                    report_errors(root, &message, &errors);
+                }
+                sender.input(TrashInput::Refresh);
+            }
+        }
+    }
+}